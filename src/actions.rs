@@ -0,0 +1,343 @@
+use bevy::input::InputSystems;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::combat::ActiveCombat;
+use crate::events::ActiveEvent;
+use crate::game::GameState;
+use crate::store::Store;
+use crate::upgrades::UpgradeScreen;
+
+pub struct ActionsPlugin;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionSystemSet;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Bindings::load_or_report(&bindings_path()))
+            .insert_resource(Actions::default())
+            .configure_sets(PreUpdate, ActionSystemSet.after(InputSystems))
+            .add_systems(PreUpdate, read_actions.in_set(ActionSystemSet));
+    }
+}
+
+pub const BINDINGS_FILE: &str = "assets/bindings.ron";
+// Used when the bindings file is missing or broken
+const DEFAULT_BINDINGS: &str = include_str!("../assets/bindings.ron");
+
+// Resolves the bindings file next to the crate when run through cargo
+pub fn bindings_path() -> PathBuf {
+    match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(root) => PathBuf::from(root).join(BINDINGS_FILE),
+        Err(_) => PathBuf::from(BINDINGS_FILE),
+    }
+}
+
+// Everything the player can ask the game to do. Keys and buttons bound to
+// `Number(n)` are routed to whatever the screen on display uses numbers for;
+// the routed actions count from 0, like the lists they index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Action {
+    Number(usize),
+    ChooseOption(usize),
+    TravelTo(usize),
+    Buy(usize),
+    Upgrade(usize),
+    MenuOption(usize),
+    Pause,
+    Confirm,
+    Cancel,
+    ToggleStore,
+    ToggleUpgrades,
+    CycleTarget,
+    ToggleAutofire,
+    Fire,
+    SelectPreviousSystem,
+    SelectNextSystem,
+    AddPower,
+    RemovePower,
+    CycleCrew,
+    AssignCrew,
+    Quicksave,
+    Quickload,
+    LoadAutosave,
+}
+
+impl Action {
+    fn index(&self) -> Option<usize> {
+        match self {
+            Action::ChooseOption(index)
+            | Action::TravelTo(index)
+            | Action::Buy(index)
+            | Action::Upgrade(index)
+            | Action::MenuOption(index) => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+// Actions performed this frame, already routed for the screen on display
+#[derive(Resource, Default)]
+pub struct Actions {
+    performed: Vec<Action>,
+}
+
+impl Actions {
+    pub fn just_performed(&self, action: Action) -> bool {
+        self.performed.contains(&action)
+    }
+
+    // Index picked with a numbered action, e.g. `index_of(Action::Buy)`
+    pub fn index_of(&self, numbered: fn(usize) -> Action) -> Option<usize> {
+        self.performed.iter()
+            .find_map(|action| action.index().filter(|index| numbered(*index) == *action))
+    }
+
+    // Lets something other than a keyboard or gamepad (an autopilot, a test) act
+    pub fn perform(&mut self, action: Action) {
+        if !self.performed.contains(&action) {
+            self.performed.push(action);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, line: usize, column: usize, message: String },
+    UnknownKey(String),
+    UnknownButton(String),
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            BindingsError::Parse { path, line, column, message } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+            BindingsError::UnknownKey(name) => write!(f, "unknown key \"{}\"", name),
+            BindingsError::UnknownButton(name) => write!(f, "unknown gamepad button \"{}\"", name),
+        }
+    }
+}
+
+// Layout of the bindings file; keys and buttons go by their Bevy names
+#[derive(Deserialize)]
+struct BindingsFile {
+    #[serde(default)]
+    keyboard: Vec<(String, Action)>,
+    #[serde(default)]
+    gamepad: Vec<(String, Action)>,
+}
+
+#[derive(Resource)]
+pub struct Bindings {
+    keyboard: Vec<(KeyCode, Action)>,
+    gamepad: Vec<(GamepadButton, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self::parse(DEFAULT_BINDINGS, Path::new(BINDINGS_FILE)).expect("built-in bindings are valid")
+    }
+}
+
+impl Bindings {
+    pub fn load(path: &Path) -> Result<Self, BindingsError> {
+        let contents = fs::read_to_string(path).map_err(|error| BindingsError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Self::parse(&contents, path)
+    }
+
+    pub fn load_or_report(path: &Path) -> Self {
+        match Self::load(path) {
+            Ok(bindings) => bindings,
+            Err(error) => {
+                eprintln!("Bindings error: {} - using the default controls", error);
+                Self::default()
+            }
+        }
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self, BindingsError> {
+        let file: BindingsFile = ron::from_str(contents).map_err(|error| BindingsError::Parse {
+            path: path.to_path_buf(),
+            line: error.span.start.line,
+            column: error.span.start.col,
+            message: error.code.to_string(),
+        })?;
+
+        let keyboard = file.keyboard.into_iter()
+            .map(|(name, action)| key_named(&name).map(|key| (key, action)).ok_or(BindingsError::UnknownKey(name)))
+            .collect::<Result<_, _>>()?;
+        let gamepad = file.gamepad.into_iter()
+            .map(|(name, action)| button_named(&name).map(|button| (button, action)).ok_or(BindingsError::UnknownButton(name)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { keyboard, gamepad })
+    }
+
+    // Short name of the first key bound to an action, for on-screen hints
+    pub fn label(&self, action: Action) -> String {
+        let Some((key, _)) = self.keyboard.iter().find(|(_, bound)| *bound == action) else {
+            return "-".to_string();
+        };
+        let name = format!("{:?}", key);
+        match name.as_str() {
+            "Escape" => "ESC".to_string(),
+            _ => name.trim_start_matches("Key").trim_start_matches("Digit").to_string(),
+        }
+    }
+
+    // Keys of every bound number, e.g. "1-9"
+    pub fn number_label(&self) -> String {
+        let highest = self.keyboard.iter()
+            .filter_map(|(_, action)| match action {
+                Action::Number(number) => Some(*number),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(1);
+        format!("{}-{}", self.label(Action::Number(1)), self.label(Action::Number(highest)))
+    }
+}
+
+// Keys that can be named in the bindings file
+const BINDABLE_KEYS: [KeyCode; 61] = [
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Escape, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight,
+];
+
+fn key_named(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|key| format!("{:?}", key) == name)
+}
+
+fn button_named(name: &str) -> Option<GamepadButton> {
+    GamepadButton::all().into_iter().find(|button| format!("{:?}", button) == name)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn read_actions(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    bindings: Res<Bindings>,
+    mut actions: ResMut<Actions>,
+    state: Res<State<GameState>>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+    store: Res<Store>,
+    upgrade_screen: Res<UpgradeScreen>,
+) {
+    actions.performed.clear();
+
+    let mut bound: Vec<Action> = bindings.keyboard.iter()
+        .filter(|(key, _)| keyboard.just_pressed(*key))
+        .map(|(_, action)| *action)
+        .collect();
+    for gamepad in &gamepads {
+        bound.extend(bindings.gamepad.iter()
+            .filter(|(button, _)| gamepad.just_pressed(*button))
+            .map(|(_, action)| *action));
+    }
+
+    for action in bound {
+        let routed = match action {
+            Action::Number(number) => route_number(number, state.get(), &active_event, &active_combat, &store, &upgrade_screen),
+            action => Some(action),
+        };
+        if let Some(routed) = routed {
+            actions.perform(routed);
+        }
+    }
+}
+
+// Each frame a number goes to exactly one screen, so nothing handles it twice
+fn route_number(
+    number: usize,
+    state: &GameState,
+    active_event: &ActiveEvent,
+    active_combat: &ActiveCombat,
+    store: &Store,
+    upgrade_screen: &UpgradeScreen,
+) -> Option<Action> {
+    let index = number.checked_sub(1)?;
+    match state {
+        GameState::MainMenu | GameState::NewRun | GameState::Settings | GameState::Paused => {
+            return Some(Action::MenuOption(index));
+        }
+        GameState::GameOver | GameState::Victory => return None,
+        GameState::Playing => {}
+    }
+    if active_combat.is_active() {
+        None
+    } else if active_event.event.is_some() {
+        Some(Action::ChooseOption(index))
+    } else if upgrade_screen.open {
+        Some(Action::Upgrade(index))
+    } else if store.open {
+        Some(Action::Buy(index))
+    } else {
+        Some(Action::TravelTo(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_bindings_parse() {
+        let bindings = Bindings::load(&bindings_path()).unwrap();
+        assert_eq!(bindings.number_label(), "1-9");
+        assert_eq!(bindings.label(Action::Pause), "ESC");
+    }
+
+    #[test]
+    fn unknown_key_is_rejected() {
+        let result = Bindings::parse(r#"(keyboard: [("KeyAlpha", Pause)])"#, Path::new("test.ron"));
+        assert!(matches!(result, Err(BindingsError::UnknownKey(name)) if name == "KeyAlpha"));
+    }
+
+    #[test]
+    fn numbers_route_to_the_screen_on_display() {
+        let mut active_event = ActiveEvent::default();
+        let active_combat = ActiveCombat::default();
+        let mut store = Store::default();
+        let upgrade_screen = UpgradeScreen::default();
+
+        let route = |event: &ActiveEvent, store: &Store| {
+            route_number(4, &GameState::Playing, event, &active_combat, store, &upgrade_screen)
+        };
+        assert_eq!(route(&active_event, &store), Some(Action::TravelTo(3)));
+        store.open = true;
+        assert_eq!(route(&active_event, &store), Some(Action::Buy(3)));
+        active_event.event = Some(crate::events::GameEvent {
+            _event_type: crate::events::GameEventType::Story,
+            title: String::new(),
+            description: String::new(),
+            choices: Vec::new(),
+            _faction: None,
+        });
+        assert_eq!(route(&active_event, &store), Some(Action::ChooseOption(3)));
+        assert_eq!(
+            route_number(4, &GameState::Paused, &active_event, &active_combat, &store, &upgrade_screen),
+            Some(Action::MenuOption(3)),
+        );
+    }
+}
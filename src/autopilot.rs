@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::actions::{Action, ActionSystemSet, Actions};
+use crate::combat::{ActiveCombat, WeaponControl};
+use crate::crew::CrewRoster;
+use crate::events::{ActiveEvent, GameEvent, requirement_failures};
+use crate::game::{GameData, GameState};
+use crate::rng::RunRng;
+use crate::sector::{SectorMap, SectorType};
+use crate::ship::{AdjustPower, PlayerShip, PowerDistribution, Ship, SystemKind};
+use crate::store::{Store, StoreItemKind};
+use crate::upgrades::UpgradeScreen;
+
+// Plays a run on its own by performing the actions a player would, so every
+// rule goes through the same systems as in a normal game
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_autopilot)
+            .add_systems(PreUpdate, (drive_ship, restore_power)
+                .after(ActionSystemSet)
+                .run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Fuel the autopilot tops up to at stations, and heads for stations below
+const FUEL_RESERVE: f32 = 8.0;
+// Hull fraction below which the autopilot pays for repairs
+const REPAIR_BELOW: f32 = 0.6;
+const MISSILE_RESERVE: u32 = 2;
+// Systems that get spare reactor power back first, in order
+const POWER_PRIORITY: [SystemKind; 3] = [SystemKind::Weapons, SystemKind::Shields, SystemKind::Engines];
+
+// Its own rolls come from the run seed but not from the game's streams, so
+// the autopilot's decisions don't shift the galaxy it is flying through
+#[derive(Resource)]
+pub struct Autopilot {
+    rng: StdRng,
+    // Station whose store it has already been through
+    shopped_at: Option<u32>,
+}
+
+fn setup_autopilot(mut commands: Commands, run_rng: Res<RunRng>) {
+    commands.insert_resource(Autopilot { rng: StdRng::seed_from_u64(run_rng.seed), shopped_at: None });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drive_ship(
+    mut actions: ResMut<Actions>,
+    mut autopilot: ResMut<Autopilot>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+    weapon_control: Res<WeaponControl>,
+    store: Res<Store>,
+    upgrade_screen: Res<UpgradeScreen>,
+    game_data: Res<GameData>,
+    crew: Res<CrewRoster>,
+    sector_map: Res<SectorMap>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+) {
+    let Ok(ship) = player_query.single() else { return; };
+    let current_id = sector_map.current_sector_id;
+    let unvisited_store = store.sector_id == Some(current_id)
+        && !store.items.is_empty()
+        && autopilot.shopped_at != Some(current_id);
+
+    let action = if active_combat.is_active() {
+        combat_action(&active_combat, &weapon_control)
+    } else if let Some(event) = &active_event.event {
+        choice_action(event, &game_data, &crew, &mut autopilot.rng)
+    } else if upgrade_screen.open {
+        Some(Action::ToggleUpgrades)
+    } else if store.open {
+        store_action(&store, &game_data, ship)
+    } else if unvisited_store {
+        // Docking doesn't open the store, so it asks once per station
+        autopilot.shopped_at = Some(current_id);
+        Some(Action::ToggleStore)
+    } else {
+        travel_action(&sector_map, &store, &game_data, &mut autopilot.rng)
+    };
+
+    if let Some(action) = action {
+        actions.perform(action);
+    }
+}
+
+// Damage and ion hits take power out of systems; whatever the reactor has
+// spare goes back into them as soon as they can take it
+fn restore_power(
+    power: Res<PowerDistribution>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    mut power_writer: MessageWriter<AdjustPower>,
+) {
+    let Ok(ship) = player_query.single() else { return; };
+    let mut free = power.total_power.saturating_sub(ship.systems.total_power_allocated());
+    for system in POWER_PRIORITY {
+        if free == 0 {
+            break;
+        }
+        let module = ship.systems.module(system);
+        if module.power_allocated < module.capacity() {
+            power_writer.write(AdjustPower { system, delta: 1 });
+            free -= 1;
+        }
+    }
+}
+
+// Leaves the guns on autofire and takes every surrender it is offered
+fn combat_action(active_combat: &ActiveCombat, weapon_control: &WeaponControl) -> Option<Action> {
+    if active_combat.surrender_offer.is_some() {
+        Some(Action::Confirm)
+    } else if !weapon_control.autofire {
+        Some(Action::ToggleAutofire)
+    } else {
+        None
+    }
+}
+
+// Any choice the ship can afford, picked at random
+fn choice_action(event: &GameEvent, game_data: &GameData, crew: &CrewRoster, rng: &mut impl Rng) -> Option<Action> {
+    let available: Vec<usize> = event.choices.iter().enumerate()
+        .filter(|(_, choice)| requirement_failures(&choice.requirements, game_data, crew).is_empty())
+        .map(|(index, _)| index)
+        .collect();
+    if available.is_empty() {
+        return None;
+    }
+    Some(Action::ChooseOption(available[rng.gen_range(0..available.len())]))
+}
+
+// Buys fuel, repairs and missiles it is short of, then undocks
+fn store_action(store: &Store, game_data: &GameData, ship: &Ship) -> Option<Action> {
+    for (index, item) in store.items.iter().enumerate() {
+        if item.sold || item.cost(ship) > game_data.scrap {
+            continue;
+        }
+        let wanted = match item.kind {
+            StoreItemKind::Fuel(_) => game_data.fuel < FUEL_RESERVE,
+            StoreItemKind::HullRepair => ship.hull < ship.max_hull * REPAIR_BELOW,
+            StoreItemKind::Missiles(_) => ship.missiles < MISSILE_RESERVE,
+            _ => false,
+        };
+        if wanted {
+            return Some(Action::Buy(index));
+        }
+    }
+    Some(Action::ToggleStore)
+}
+
+// Always jumps deeper into the map, making for a station when fuel runs low
+fn travel_action(sector_map: &SectorMap, store: &Store, game_data: &GameData, rng: &mut impl Rng) -> Option<Action> {
+    if game_data.fuel < 1.0 {
+        return None;
+    }
+    let current_id = sector_map.current_sector_id;
+    let current = sector_map.sectors.get(&current_id)?;
+    // Wait a frame for a station's store to open, so the autopilot gets to shop
+    if current.sector_type == SectorType::Station && store.sector_id != Some(current_id) {
+        return None;
+    }
+
+    // Later layers always have higher ids; the exit jump happens on its own
+    let forward: Vec<(usize, u32)> = current.connections.iter().copied().enumerate()
+        .filter(|(_, id)| *id > current_id && sector_map.sectors.contains_key(id))
+        .collect();
+    let stations: Vec<(usize, u32)> = forward.iter().copied()
+        .filter(|(_, id)| sector_map.sectors[id].sector_type == SectorType::Station)
+        .collect();
+
+    let options = if game_data.fuel < FUEL_RESERVE && !stations.is_empty() { stations } else { forward };
+    if options.is_empty() {
+        return None;
+    }
+    let (index, _) = options[rng.gen_range(0..options.len())];
+    Some(Action::TravelTo(index))
+}
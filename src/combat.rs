@@ -0,0 +1,302 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::actions::{Action, Actions};
+use crate::crew::CrewRoster;
+use crate::enemy::{AiState, EnemyAi, build_enemy_ship};
+use crate::factions::{Faction, ShipClass, roll_ship_class};
+use crate::game::{GameData, GameState};
+use crate::rng::RunRng;
+use crate::sector::SectorMap;
+use crate::ship::{PlayerShip, Ship, ShipDamage, SystemKind};
+
+pub struct CombatPlugin;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CombatSystemSet;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_message::<StartCombat>()
+            .add_message::<CombatEnded>()
+            .insert_resource(ActiveCombat::default())
+            .insert_resource(WeaponControl::default())
+            .add_systems(Update, (
+                start_combat,
+                handle_targeting_input,
+                handle_fire_input,
+                fire_weapons,
+                check_combat_end,
+            ).chain().in_set(CombatSystemSet).run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Chance for any shot to miss its target
+const BASE_EVASION: f64 = 0.1;
+// Extra chance to dodge for every active engines level
+const EVASION_PER_ENGINE_LEVEL: f64 = 0.05;
+
+#[derive(Component)]
+pub struct EnemyShip;
+
+#[derive(Message, Clone)]
+pub struct StartCombat {
+    pub enemy_faction: Faction,
+    pub difficulty: u32,
+    // Rolled from the distance travelled when not given
+    pub ship_class: Option<ShipClass>,
+}
+
+#[derive(Message, Clone)]
+pub struct CombatEnded {
+    pub enemy_faction: Faction,
+    pub result: CombatResult,
+    pub salvage: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CombatResult {
+    Victory,
+    Defeat,
+    // The enemy jumped away before it could be finished off
+    Escaped,
+    // The enemy gave up and paid for its life
+    Surrendered,
+}
+
+// What a beaten enemy hands over in exchange for mercy
+#[derive(Clone)]
+pub struct SurrenderOffer {
+    pub scrap: u32,
+    pub crew: Option<String>,
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveCombat {
+    pub enemy: Option<Entity>,
+    pub enemy_faction: Option<Faction>,
+    pub enemy_class: Option<ShipClass>,
+    pub difficulty: u32,
+    // Enemy system the player's weapons aim at (None spreads hits at random)
+    pub player_target: Option<SystemKind>,
+    // Terms the enemy is waiting on an answer to
+    pub surrender_offer: Option<SurrenderOffer>,
+}
+
+// How the player's weapons fire: on their own as soon as they charge, or on command
+#[derive(Resource)]
+pub struct WeaponControl {
+    pub autofire: bool,
+    pub fire_requested: bool,
+}
+
+impl Default for WeaponControl {
+    fn default() -> Self {
+        Self { autofire: true, fire_requested: false }
+    }
+}
+
+impl ActiveCombat {
+    pub fn is_active(&self) -> bool {
+        self.enemy.is_some()
+    }
+
+    // Scrap left in the wreck of the current enemy
+    pub fn salvage_value(&self) -> u32 {
+        // Bigger ships leave more to pick through
+        let class_bonus = self.enemy_class.map(|class| class.tier() * 4).unwrap_or(0);
+        5 + self.difficulty * 3 + class_bonus
+    }
+}
+
+fn start_combat(
+    mut commands: Commands,
+    mut start_reader: MessageReader<StartCombat>,
+    mut active_combat: ResMut<ActiveCombat>,
+    sector_map: Res<SectorMap>,
+    mut run_rng: ResMut<RunRng>,
+) {
+    for start in start_reader.read() {
+        // Only one fight at a time
+        if active_combat.is_active() {
+            continue;
+        }
+
+        let class = start.ship_class
+            .unwrap_or_else(|| roll_ship_class(sector_map.distance_traveled, &mut run_rng.combat));
+        let (ship, shields) = build_enemy_ship(&start.enemy_faction, class, start.difficulty);
+        let ai = EnemyAi::new(&start.enemy_faction, &ship);
+        let enemy = commands.spawn((
+            EnemyShip,
+            start.enemy_faction.clone(),
+            ship,
+            shields,
+            ai,
+        )).id();
+
+        println!("Combat initiated with a {} {} (difficulty: {})!", start.enemy_faction.name(), class.name(), start.difficulty);
+
+        active_combat.enemy = Some(enemy);
+        active_combat.enemy_faction = Some(start.enemy_faction.clone());
+        active_combat.enemy_class = Some(class);
+        active_combat.difficulty = start.difficulty;
+    }
+}
+
+fn handle_targeting_input(
+    actions: Res<Actions>,
+    mut active_combat: ResMut<ActiveCombat>,
+) {
+    if !active_combat.is_active() || !actions.just_performed(Action::CycleTarget) {
+        return;
+    }
+
+    // Cycles random -> each system in turn -> random
+    active_combat.player_target = match active_combat.player_target {
+        None => Some(SystemKind::ALL[0]),
+        Some(current) => SystemKind::ALL.iter()
+            .position(|system| *system == current)
+            .and_then(|index| SystemKind::ALL.get(index + 1))
+            .copied(),
+    };
+    match active_combat.player_target {
+        Some(system) => println!("Targeting enemy {}.", system.name()),
+        None => println!("Targeting at random."),
+    }
+}
+
+fn handle_fire_input(
+    actions: Res<Actions>,
+    mut control: ResMut<WeaponControl>,
+    active_combat: Res<ActiveCombat>,
+) {
+    if actions.just_performed(Action::ToggleAutofire) {
+        control.autofire = !control.autofire;
+        println!("Autofire {}.", if control.autofire { "on" } else { "off - fire by hand" });
+    }
+    if actions.just_performed(Action::Fire) && active_combat.is_active() {
+        control.fire_requested = true;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fire_weapons(
+    active_combat: Res<ActiveCombat>,
+    mut control: ResMut<WeaponControl>,
+    mut player_query: Query<(Entity, &mut Ship), (With<PlayerShip>, Without<EnemyShip>)>,
+    mut enemy_query: Query<(&mut Ship, &EnemyAi), (With<EnemyShip>, Without<PlayerShip>)>,
+    mut damage_writer: MessageWriter<ShipDamage>,
+    mut run_rng: ResMut<RunRng>,
+) {
+    let Some(enemy_entity) = active_combat.enemy else { return; };
+    let Ok((player_entity, mut player_ship)) = player_query.single_mut() else { return; };
+    let Ok((mut enemy_ship, ai)) = enemy_query.get_mut(enemy_entity) else { return; };
+
+    let player_evasion = evasion(&player_ship);
+    let enemy_evasion = evasion(&enemy_ship);
+    // Held weapons keep their charge until the player gives the order
+    let player_fires = control.autofire || control.fire_requested;
+    control.fire_requested = false;
+    if player_fires {
+        fire_charged_weapons(&mut player_ship, enemy_entity, enemy_evasion, active_combat.player_target, &mut damage_writer, &mut run_rng.combat);
+    }
+    // A ship waiting on an answer to its surrender holds its fire
+    if ai.state != AiState::Surrendering {
+        fire_charged_weapons(&mut enemy_ship, player_entity, player_evasion, ai.target, &mut damage_writer, &mut run_rng.combat);
+    }
+}
+
+// A ship with its engines offline can't dodge at all
+pub fn evasion(ship: &Ship) -> f64 {
+    match ship.systems.engines.active_level() {
+        0 => 0.0,
+        level => BASE_EVASION + EVASION_PER_ENGINE_LEVEL * level as f64,
+    }
+}
+
+fn fire_charged_weapons(
+    ship: &mut Ship,
+    target: Entity,
+    target_evasion: f64,
+    aimed_system: Option<SystemKind>,
+    damage_writer: &mut MessageWriter<ShipDamage>,
+    rng: &mut impl Rng,
+) {
+    let Ship { weapons, missiles, .. } = ship;
+    for weapon in weapons.iter_mut() {
+        if !weapon.is_charged() {
+            continue;
+        }
+        // Launchers stay loaded and waiting until there is ammo for them
+        if weapon.weapon_type.uses_missiles() {
+            if *missiles == 0 {
+                continue;
+            }
+            *missiles -= 1;
+        }
+        weapon.current_charge = 0.0;
+
+        for _ in 0..weapon.weapon_type.shots() {
+            if rng.gen_bool(target_evasion.min(1.0)) {
+                println!("A {} shot misses!", weapon.weapon_type.name());
+                continue;
+            }
+            let system = aimed_system
+                .unwrap_or_else(|| SystemKind::ALL[rng.gen_range(0..SystemKind::ALL.len())]);
+            damage_writer.write(ShipDamage {
+                target,
+                amount: weapon.weapon_type.damage(),
+                system: Some(system),
+                weapon: weapon.weapon_type,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_combat_end(
+    mut commands: Commands,
+    mut active_combat: ResMut<ActiveCombat>,
+    mut game_data: ResMut<GameData>,
+    mut crew: ResMut<CrewRoster>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    enemy_query: Query<(&Ship, &EnemyAi), With<EnemyShip>>,
+    mut ended_writer: MessageWriter<CombatEnded>,
+    mut run_rng: ResMut<RunRng>,
+) {
+    let Some(enemy_entity) = active_combat.enemy else { return; };
+    let Some(enemy_faction) = active_combat.enemy_faction.clone() else { return; };
+
+    let player_destroyed = player_query.single().map(|ship| ship.is_destroyed()).unwrap_or(false);
+    let enemy_destroyed = enemy_query.get(enemy_entity).map(|(ship, _)| ship.is_destroyed()).unwrap_or(true);
+    let enemy_state = enemy_query.get(enemy_entity).map(|(_, ai)| ai.state).unwrap_or(AiState::Fighting);
+
+    let (result, salvage) = if player_destroyed {
+        println!("Your ship was destroyed by the {}!", enemy_faction.name());
+        (CombatResult::Defeat, 0)
+    } else if enemy_destroyed {
+        let salvage = active_combat.salvage_value();
+        game_data.scrap += salvage;
+        println!("Victory! The {} ship is destroyed. Salvaged {} scrap.", enemy_faction.name(), salvage);
+        (CombatResult::Victory, salvage)
+    } else if enemy_state == AiState::Fled {
+        println!("The {} ship jumped away.", enemy_faction.name());
+        (CombatResult::Escaped, 0)
+    } else if enemy_state == AiState::Surrendered {
+        let offer = active_combat.surrender_offer.clone().unwrap_or(SurrenderOffer { scrap: 0, crew: None });
+        game_data.scrap += offer.scrap;
+        println!("The {} hand over {} scrap and limp away.", enemy_faction.name(), offer.scrap);
+        if let Some(name) = &offer.crew {
+            crew.recruit(name, &mut run_rng.combat);
+        }
+        (CombatResult::Surrendered, offer.scrap)
+    } else {
+        return;
+    };
+
+    if let Ok(mut entity) = commands.get_entity(enemy_entity) {
+        entity.despawn();
+    }
+    *active_combat = ActiveCombat::default();
+    ended_writer.write(CombatEnded { enemy_faction, result, salvage });
+}
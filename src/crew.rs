@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::game::RunStart;
+use crate::ship::SystemKind;
+
+pub struct CrewPlugin;
+
+impl Plugin for CrewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(RunStart, setup_crew);
+    }
+}
+
+// Highest level any crew skill can reach
+pub const MAX_SKILL_LEVEL: u32 = 5;
+// Uses needed per current level before a skill improves
+const EXPERIENCE_PER_LEVEL: u32 = 3;
+pub const MAX_CREW_HEALTH: f32 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Skill {
+    Piloting,
+    Diplomacy,
+    Science,
+    Sensors,
+    Engines,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 5] = [
+        Skill::Piloting,
+        Skill::Diplomacy,
+        Skill::Science,
+        Skill::Sensors,
+        Skill::Engines,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::Piloting => "piloting",
+            Skill::Diplomacy => "diplomacy",
+            Skill::Science => "science",
+            Skill::Sensors => "sensors",
+            Skill::Engines => "engines",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CrewMember {
+    pub name: String,
+    pub skills: HashMap<Skill, u32>,
+    pub experience: HashMap<Skill, u32>,
+    #[serde(default = "full_health")]
+    pub health: f32,
+    // System the crew member is stationed at and keeps repaired
+    #[serde(default)]
+    pub station: Option<SystemKind>,
+}
+
+fn full_health() -> f32 {
+    MAX_CREW_HEALTH
+}
+
+impl CrewMember {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            skills: Skill::ALL.iter().map(|skill| (*skill, 1)).collect(),
+            experience: HashMap::new(),
+            health: MAX_CREW_HEALTH,
+            station: None,
+        }
+    }
+
+    pub fn at_station(mut self, system: SystemKind) -> Self {
+        self.station = Some(system);
+        self
+    }
+
+    pub fn with_skill(mut self, skill: Skill, level: u32) -> Self {
+        self.skills.insert(skill, level.min(MAX_SKILL_LEVEL));
+        self
+    }
+
+    pub fn skill_level(&self, skill: Skill) -> u32 {
+        self.skills.get(&skill).copied().unwrap_or(0)
+    }
+
+    // Returns true when the use pushed the skill up a level
+    pub fn gain_experience(&mut self, skill: Skill) -> bool {
+        let level = self.skill_level(skill);
+        if level >= MAX_SKILL_LEVEL {
+            return false;
+        }
+
+        let experience = self.experience.entry(skill).or_insert(0);
+        *experience += 1;
+        if *experience >= level * EXPERIENCE_PER_LEVEL {
+            *experience = 0;
+            self.skills.insert(skill, level + 1);
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct CrewRoster {
+    pub members: Vec<CrewMember>,
+}
+
+impl CrewRoster {
+    pub fn best_level(&self, skill: Skill) -> u32 {
+        self.members.iter().map(|member| member.skill_level(skill)).max().unwrap_or(0)
+    }
+
+    // The most skilled crew member handles the task and learns from it
+    pub fn use_skill(&mut self, skill: Skill) {
+        let best = self.members.iter_mut().max_by_key(|member| member.skill_level(skill));
+        if let Some(member) = best {
+            if member.gain_experience(skill) {
+                println!("{} improved {} to level {}!", member.name, skill.name(), member.skill_level(skill));
+            }
+        }
+    }
+
+    pub fn stationed_at(&self, system: SystemKind) -> usize {
+        self.members.iter().filter(|member| member.station == Some(system)).count()
+    }
+
+    // Drops crew members whose health has run out
+    pub fn remove_dead(&mut self, cause: &str) {
+        self.members.retain(|member| {
+            if member.health <= 0.0 {
+                println!("{} {}.", member.name, cause);
+            }
+            member.health > 0.0
+        });
+    }
+
+    pub fn recruit(&mut self, name: &str, rng: &mut impl Rng) {
+        // New recruits bring one stronger specialty
+        let specialty = Skill::ALL[rng.gen_range(0..Skill::ALL.len())];
+        let member = CrewMember::new(name).with_skill(specialty, 2);
+        println!("New crew member joined: {} ({} specialist)", member.name, specialty.name());
+        self.members.push(member);
+    }
+}
+
+fn setup_crew(mut commands: Commands) {
+    // Starting crew of the drifter
+    commands.insert_resource(CrewRoster {
+        members: vec![
+            CrewMember::new("Captain Vex")
+                .with_skill(Skill::Piloting, 2)
+                .at_station(SystemKind::Engines),
+            CrewMember::new("Lyra Sol")
+                .with_skill(Skill::Diplomacy, 2)
+                .with_skill(Skill::Science, 2)
+                .at_station(SystemKind::Shields),
+            CrewMember::new("Kade-7")
+                .with_skill(Skill::Engines, 2)
+                .at_station(SystemKind::Weapons),
+        ],
+    });
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use crate::actions::{Action, Actions};
+use crate::crew::CrewRoster;
+use crate::game::GameState;
+use crate::ship::{PlayerShip, PowerSelection, Ship, SystemKind, MAX_SYSTEM_HEALTH};
+
+pub struct DamageControlPlugin;
+
+impl Plugin for DamageControlPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(CrewSelection::default())
+            .add_systems(Update, (
+                handle_crew_assignment,
+                burn_fires,
+                repair_systems,
+            ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// System health lost per second to a fire in its compartment
+const FIRE_SYSTEM_DAMAGE: f32 = 4.0;
+// Health lost per second by each crew member stationed in a burning compartment
+const FIRE_CREW_DAMAGE: f32 = 3.0;
+// Crew-seconds of work to put out a fire or seal a breach
+const EXTINGUISH_TIME: f32 = 3.0;
+const SEAL_TIME: f32 = 5.0;
+// System health restored per second by each stationed crew member
+const REPAIR_RATE: f32 = 10.0;
+
+// Crew member highlighted on the crew panel for station assignment
+#[derive(Resource, Default)]
+pub struct CrewSelection {
+    pub selected: usize,
+}
+
+fn handle_crew_assignment(
+    actions: Res<Actions>,
+    mut crew_selection: ResMut<CrewSelection>,
+    power_selection: Res<PowerSelection>,
+    mut crew: ResMut<CrewRoster>,
+) {
+    if crew.members.is_empty() {
+        return;
+    }
+
+    if actions.just_performed(Action::CycleCrew) {
+        crew_selection.selected = (crew_selection.selected + 1) % crew.members.len();
+    }
+
+    // Sends the selected crew member to the system highlighted on the power panel
+    if actions.just_performed(Action::AssignCrew) {
+        let system = power_selection.system();
+        let index = crew_selection.selected % crew.members.len();
+        let member = &mut crew.members[index];
+        if member.station == Some(system) {
+            member.station = None;
+            println!("{} left the {} station.", member.name, system.name());
+        } else {
+            member.station = Some(system);
+            println!("{} is now stationed at {}.", member.name, system.name());
+        }
+    }
+}
+
+fn burn_fires(
+    mut player_query: Query<&mut Ship, With<PlayerShip>>,
+    mut crew: ResMut<CrewRoster>,
+    time: Res<Time>,
+) {
+    let Ok(mut ship) = player_query.single_mut() else { return; };
+    let burning: Vec<SystemKind> = SystemKind::ALL.into_iter()
+        .filter(|system| ship.systems.module(*system).hazards.fire)
+        .collect();
+    if burning.is_empty() {
+        return;
+    }
+
+    let delta = time.delta_secs();
+    for system in &burning {
+        let module = ship.systems.module_mut(*system);
+        module.health = (module.health - FIRE_SYSTEM_DAMAGE * delta).max(0.0);
+    }
+
+    if crew.members.iter().any(|member| member.station.is_some_and(|station| burning.contains(&station))) {
+        for member in &mut crew.members {
+            if member.station.is_some_and(|station| burning.contains(&station)) {
+                member.health = (member.health - FIRE_CREW_DAMAGE * delta).max(0.0);
+            }
+        }
+        crew.remove_dead("died fighting a fire");
+    }
+}
+
+fn repair_systems(
+    mut player_query: Query<&mut Ship, With<PlayerShip>>,
+    crew: Res<CrewRoster>,
+    time: Res<Time>,
+) {
+    let Ok(mut ship) = player_query.single_mut() else { return; };
+    let delta = time.delta_secs();
+
+    for system in SystemKind::ALL {
+        let workers = crew.stationed_at(system) as f32;
+        if workers == 0.0 {
+            continue;
+        }
+
+        // Crew deal with fires first, then breaches, then patch up the system itself
+        let module = ship.systems.module_mut(system);
+        if module.hazards.fire {
+            module.hazards.progress += workers * delta;
+            if module.hazards.progress >= EXTINGUISH_TIME {
+                module.hazards.fire = false;
+                module.hazards.progress = 0.0;
+                println!("The fire in {} is out.", system.name());
+            }
+        } else if module.hazards.breach {
+            module.hazards.progress += workers * delta;
+            if module.hazards.progress >= SEAL_TIME {
+                module.hazards.breach = false;
+                module.hazards.progress = 0.0;
+                println!("The breach in {} is sealed.", system.name());
+            }
+        } else if module.health < MAX_SYSTEM_HEALTH {
+            module.health = (module.health + REPAIR_RATE * workers * delta).min(MAX_SYSTEM_HEALTH);
+        }
+    }
+}
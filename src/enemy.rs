@@ -0,0 +1,394 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::actions::{Action, Actions};
+use crate::combat::{ActiveCombat, CombatSystemSet, EnemyShip, SurrenderOffer};
+use crate::factions::{Faction, ShipClass};
+use crate::game::GameState;
+use crate::rng::RunRng;
+use crate::ship::{PlayerShip, Ship, ShipSystems, Shields, SystemKind, SystemModule, Weapon, WeaponType, MAX_SYSTEM_LEVEL, MAX_WEAPONS};
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            choose_enemy_target,
+            update_enemy_morale,
+            reroute_enemy_power,
+            handle_surrender_response,
+        ).chain().before(CombatSystemSet).run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Engine-seconds an enemy needs to spool up its FTL drive and jump away
+const ESCAPE_CHARGE: f32 = 20.0;
+// Seconds between enemy power reroutes
+const REROUTE_INTERVAL: f32 = 2.0;
+// Chance a surrendering crew throws in one of their own as part of the deal
+const CREW_OFFER_CHANCE: f64 = 0.3;
+
+const WEAPONS_FIRST: [SystemKind; 4] = [SystemKind::Weapons, SystemKind::Shields, SystemKind::Engines, SystemKind::Oxygen];
+const SHIELDS_FIRST: [SystemKind; 4] = [SystemKind::Shields, SystemKind::Weapons, SystemKind::Engines, SystemKind::Oxygen];
+const ENGINES_FIRST: [SystemKind; 3] = [SystemKind::Engines, SystemKind::Shields, SystemKind::Weapons];
+
+// How a faction's captains fight
+#[derive(Clone, Copy)]
+pub struct Personality {
+    // Player systems to knock out, in order; empty fires at random
+    targets: &'static [SystemKind],
+    // Where spare reactor power goes first
+    power_priority: &'static [SystemKind],
+    // Hull fraction below which the crew offers to surrender
+    surrender_below: Option<f32>,
+    // Hull fraction below which the ship tries to jump away
+    escape_below: Option<f32>,
+}
+
+fn faction_personality(faction: &Faction) -> Personality {
+    match faction {
+        // Pirates fold as soon as a fight turns against them
+        Faction::Spirats => Personality {
+            targets: &WEAPONS_FIRST,
+            power_priority: &ENGINES_FIRST,
+            surrender_below: Some(0.5),
+            escape_below: Some(0.25),
+        },
+        // Webes shut down the drifter's guns and cut their losses
+        Faction::Webes => Personality {
+            targets: &WEAPONS_FIRST,
+            power_priority: &SHIELDS_FIRST,
+            surrender_below: None,
+            escape_below: Some(0.3),
+        },
+        // Archs fight to the last
+        Faction::Archs => Personality {
+            targets: &SHIELDS_FIRST,
+            power_priority: &WEAPONS_FIRST,
+            surrender_below: None,
+            escape_below: None,
+        },
+        // Celestials would rather leave than fight
+        Faction::Celestials => Personality {
+            targets: &SHIELDS_FIRST,
+            power_priority: &ENGINES_FIRST,
+            surrender_below: None,
+            escape_below: Some(0.6),
+        },
+        Faction::Cosmicons => Personality {
+            targets: &SHIELDS_FIRST,
+            power_priority: &SHIELDS_FIRST,
+            surrender_below: Some(0.2),
+            escape_below: None,
+        },
+        Faction::Spades => Personality {
+            targets: &WEAPONS_FIRST,
+            power_priority: &WEAPONS_FIRST,
+            surrender_below: None,
+            escape_below: Some(0.2),
+        },
+        Faction::Neutral => Personality {
+            targets: &[],
+            power_priority: &SHIELDS_FIRST,
+            surrender_below: Some(0.6),
+            escape_below: Some(0.4),
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiState {
+    Fighting,
+    // Charging the FTL drive to get away
+    Escaping,
+    Fled,
+    // Holding fire until the player answers a surrender offer
+    Surrendering,
+    Surrendered,
+}
+
+// Brain of an enemy warship
+#[derive(Component)]
+pub struct EnemyAi {
+    pub personality: Personality,
+    pub state: AiState,
+    // Player system the enemy's weapons aim at (None spreads hits at random)
+    pub target: Option<SystemKind>,
+    pub escape_progress: f32,
+    surrender_offered: bool,
+    // Power the enemy's reactor can hand out
+    reactor: u32,
+    reroute_timer: f32,
+}
+
+impl EnemyAi {
+    pub fn new(faction: &Faction, ship: &Ship) -> Self {
+        Self {
+            personality: faction_personality(faction),
+            state: AiState::Fighting,
+            target: None,
+            escape_progress: 0.0,
+            surrender_offered: false,
+            reactor: ship.systems.total_power_allocated(),
+            reroute_timer: REROUTE_INTERVAL,
+        }
+    }
+
+    // Share of the FTL charge completed, for the combat panel
+    pub fn escape_percent(&self) -> f32 {
+        (self.escape_progress / ESCAPE_CHARGE * 100.0).min(100.0)
+    }
+}
+
+// How a faction fits out its warships
+struct Loadout {
+    // Weapons mounted in order, repeating if the hull has more mounts
+    weapons: &'static [WeaponType],
+    engine_bonus: i32,
+    shield_bonus: u32,
+    hull_multiplier: f32,
+}
+
+fn faction_loadout(faction: &Faction) -> Loadout {
+    match faction {
+        // Fast raiders that open with missiles
+        Faction::Spirats => Loadout {
+            weapons: &[WeaponType::Missile, WeaponType::Laser],
+            engine_bonus: 1,
+            shield_bonus: 0,
+            hull_multiplier: 0.8,
+        },
+        // Ion batteries that strip shields and shut systems down
+        Faction::Webes => Loadout {
+            weapons: &[WeaponType::Ion, WeaponType::Ion, WeaponType::Laser],
+            engine_bonus: 0,
+            shield_bonus: 0,
+            hull_multiplier: 0.9,
+        },
+        // Slow, heavily armoured warships
+        Faction::Archs => Loadout {
+            weapons: &[WeaponType::Laser, WeaponType::Beam, WeaponType::Missile],
+            engine_bonus: -1,
+            shield_bonus: 1,
+            hull_multiplier: 1.5,
+        },
+        Faction::Cosmicons => Loadout {
+            weapons: &[WeaponType::Laser, WeaponType::Laser, WeaponType::Ion],
+            engine_bonus: 0,
+            shield_bonus: 1,
+            hull_multiplier: 1.0,
+        },
+        Faction::Celestials => Loadout {
+            weapons: &[WeaponType::AetheriumLance, WeaponType::Ion],
+            engine_bonus: 0,
+            shield_bonus: 1,
+            hull_multiplier: 1.0,
+        },
+        Faction::Spades => Loadout {
+            weapons: &[WeaponType::Beam, WeaponType::Laser, WeaponType::Missile],
+            engine_bonus: 0,
+            shield_bonus: 0,
+            hull_multiplier: 1.1,
+        },
+        Faction::Neutral => Loadout {
+            weapons: &[WeaponType::Laser, WeaponType::Laser, WeaponType::Missile],
+            engine_bonus: 0,
+            shield_bonus: 0,
+            hull_multiplier: 1.0,
+        },
+    }
+}
+
+// Base hull, weapon mounts, shield layers and engines level of each class
+fn class_frame(class: ShipClass) -> (f32, u32, u32, u32) {
+    match class {
+        ShipClass::Scout => (6.0, 1, 1, 2),
+        ShipClass::Fighter => (9.0, 2, 1, 2),
+        ShipClass::Cruiser => (13.0, 2, 2, 1),
+        ShipClass::Battleship => (18.0, 3, 2, 1),
+        ShipClass::Flagship => (24.0, 4, 3, 2),
+    }
+}
+
+// Builds a fully crewed enemy warship; danger adds hull, shields and weapon power
+pub fn build_enemy_ship(faction: &Faction, class: ShipClass, danger: u32) -> (Ship, Shields) {
+    let loadout = faction_loadout(faction);
+    let (base_hull, mounts, base_shields, base_engines) = class_frame(class);
+
+    // Mount the faction's weapons while the weapons system can still power them,
+    // falling back to lasers when a heavy weapon won't fit
+    let weapon_power = (mounts + danger / 3).min(MAX_SYSTEM_LEVEL);
+    let mut power_used = 0;
+    let mut weapons = Vec::new();
+    for weapon_type in loadout.weapons.iter().cycle().take((mounts as usize).min(MAX_WEAPONS)) {
+        let weapon_type = if power_used + weapon_type.power() <= weapon_power {
+            *weapon_type
+        } else if power_used + WeaponType::Laser.power() <= weapon_power {
+            WeaponType::Laser
+        } else {
+            break;
+        };
+        power_used += weapon_type.power();
+        weapons.push(Weapon::new(weapon_type));
+    }
+    // Launchers run dry, so every ship keeps at least one energy weapon
+    if weapons.iter().all(|weapon| weapon.weapon_type.uses_missiles()) {
+        if let Some(last) = weapons.last_mut() {
+            *last = Weapon::new(WeaponType::Laser);
+        }
+    }
+
+    let launchers = weapons.iter().filter(|weapon| weapon.weapon_type.uses_missiles()).count() as u32;
+    let missiles = launchers * (2 + danger / 2);
+
+    let hull = ((base_hull + danger as f32 * 2.0) * loadout.hull_multiplier).round();
+    let shield_layers = (base_shields + loadout.shield_bonus + danger / 4).min(MAX_SYSTEM_LEVEL);
+    let engines = (base_engines as i32 + loadout.engine_bonus).clamp(1, MAX_SYSTEM_LEVEL as i32) as u32;
+
+    // Engines and shields carry a spare level the crew can reroute power into
+    let ship = Ship {
+        systems: ShipSystems {
+            engines: with_spare_level(engines),
+            weapons: SystemModule::with_level(power_used.max(1)),
+            shields: with_spare_level(shield_layers),
+            oxygen: SystemModule::new(),
+            medbay: SystemModule::new(),
+            sensors: SystemModule::new(),
+        },
+        weapons,
+        hull,
+        max_hull: hull,
+        missiles,
+    };
+
+    let shields = Shields {
+        current: shield_layers as f32,
+        max: shield_layers as f32,
+        recharge_rate: 0.5,
+        recharge_delay: 5.0,
+        last_hit_time: 0.0,
+    };
+
+    (ship, shields)
+}
+
+fn with_spare_level(powered: u32) -> SystemModule {
+    SystemModule {
+        level: (powered + 1).min(MAX_SYSTEM_LEVEL),
+        ..SystemModule::with_level(powered)
+    }
+}
+
+// Aims at the first system on the personality's list that is still working
+fn choose_enemy_target(
+    mut enemy_query: Query<&mut EnemyAi, With<EnemyShip>>,
+    player_query: Query<&Ship, (With<PlayerShip>, Without<EnemyShip>)>,
+) {
+    let Ok(player_ship) = player_query.single() else { return; };
+    for mut ai in enemy_query.iter_mut() {
+        let target = ai.personality.targets.iter()
+            .copied()
+            .find(|system| player_ship.systems.module(*system).is_functional());
+        if ai.target != target {
+            ai.target = target;
+        }
+    }
+}
+
+fn update_enemy_morale(
+    mut enemy_query: Query<(&Ship, &Faction, &mut EnemyAi), With<EnemyShip>>,
+    mut active_combat: ResMut<ActiveCombat>,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+) {
+    let Some(enemy_entity) = active_combat.enemy else { return; };
+    let Ok((ship, faction, mut ai)) = enemy_query.get_mut(enemy_entity) else { return; };
+    let hull_fraction = ship.hull / ship.max_hull;
+
+    match ai.state {
+        AiState::Fighting => {
+            if !ai.surrender_offered && ai.personality.surrender_below.is_some_and(|limit| hull_fraction <= limit) {
+                ai.surrender_offered = true;
+                ai.state = AiState::Surrendering;
+                let crew = run_rng.combat.gen_bool(CREW_OFFER_CHANCE)
+                    .then(|| format!("{} Defector", faction.name()));
+                let offer = SurrenderOffer { scrap: active_combat.salvage_value(), crew };
+                match &offer.crew {
+                    Some(name) => println!("The {} surrender! They offer {} scrap and {} to spare them. (Y - accept, N - refuse)", faction.name(), offer.scrap, name),
+                    None => println!("The {} surrender! They offer {} scrap to spare them. (Y - accept, N - refuse)", faction.name(), offer.scrap),
+                }
+                active_combat.surrender_offer = Some(offer);
+            } else if ai.personality.escape_below.is_some_and(|limit| hull_fraction <= limit) {
+                ai.state = AiState::Escaping;
+                println!("The {} ship is powering up its FTL drive!", faction.name());
+            } else if !has_usable_weapons(ship) {
+                // Nothing left to shoot with, so staying only gets them killed
+                ai.state = AiState::Escaping;
+                println!("The {} ship is out of weapons and powering up its FTL drive!", faction.name());
+            }
+        }
+        AiState::Escaping => {
+            // Knocking out the engines stops the jump
+            ai.escape_progress += ship.systems.engines.effective_level() * time.delta_secs();
+            if ai.escape_progress >= ESCAPE_CHARGE {
+                ai.state = AiState::Fled;
+            }
+        }
+        AiState::Surrendering | AiState::Fled | AiState::Surrendered => {}
+    }
+}
+
+// An energy weapon, or a launcher with missiles left to fire
+fn has_usable_weapons(ship: &Ship) -> bool {
+    ship.weapons.iter().any(|weapon| !weapon.weapon_type.uses_missiles() || ship.missiles > 0)
+}
+
+// Hands reactor power freed up by damage or ion hits to the systems that need it most
+fn reroute_enemy_power(
+    mut enemy_query: Query<(&mut Ship, &mut EnemyAi), With<EnemyShip>>,
+    time: Res<Time>,
+) {
+    for (mut ship, mut ai) in enemy_query.iter_mut() {
+        ai.reroute_timer -= time.delta_secs();
+        if ai.reroute_timer > 0.0 {
+            continue;
+        }
+        ai.reroute_timer = REROUTE_INTERVAL;
+
+        // A ship on the run pulls power out of its guns and into its engines
+        let priorities = if ai.state == AiState::Escaping {
+            ship.systems.weapons.power_allocated = 0;
+            &ENGINES_FIRST[..]
+        } else {
+            ai.personality.power_priority
+        };
+
+        let mut free = ai.reactor.saturating_sub(ship.systems.total_power_allocated());
+        for system in priorities {
+            let module = ship.systems.module_mut(*system);
+            let room = module.capacity().saturating_sub(module.power_allocated);
+            let added = room.min(free);
+            module.power_allocated += added;
+            free -= added;
+        }
+    }
+}
+
+fn handle_surrender_response(
+    actions: Res<Actions>,
+    mut active_combat: ResMut<ActiveCombat>,
+    mut enemy_query: Query<&mut EnemyAi, With<EnemyShip>>,
+) {
+    if active_combat.surrender_offer.is_none() {
+        return;
+    }
+    let Some(enemy_entity) = active_combat.enemy else { return; };
+    let Ok(mut ai) = enemy_query.get_mut(enemy_entity) else { return; };
+
+    if actions.just_performed(Action::Confirm) {
+        ai.state = AiState::Surrendered;
+    } else if actions.just_performed(Action::Cancel) {
+        ai.state = AiState::Fighting;
+        active_combat.surrender_offer = None;
+        println!("You refuse their surrender.");
+    }
+}
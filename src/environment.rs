@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::combat::{ActiveCombat, evasion};
+use crate::crew::CrewRoster;
+use crate::events::ActiveEvent;
+use crate::game::GameState;
+use crate::rng::RunRng;
+use crate::sector::{SectorMap, SectorType};
+use crate::ship::{PlayerShip, Ship, ShipDamage, SystemKind, WeaponType};
+
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Environment::default())
+            .add_systems(Update, (
+                update_environment,
+                strike_asteroids,
+                scramble_power,
+                drain_crew_in_rift,
+            ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Seconds between asteroid strikes
+const ASTEROID_INTERVAL: f32 = 8.0;
+const ASTEROID_DAMAGE: f32 = 1.0;
+// Seconds between anomaly power surges
+const ANOMALY_INTERVAL: f32 = 10.0;
+// Health lost per second by every crew member inside a Dark Rift
+const RIFT_CREW_DRAIN: f32 = 1.0;
+
+// Hazard of the sector the player is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hazard {
+    Nebula,
+    Asteroids,
+    Anomaly,
+    DarkRift,
+}
+
+impl Hazard {
+    pub fn from_sector_type(sector_type: &SectorType) -> Option<Self> {
+        match sector_type {
+            SectorType::Nebula => Some(Hazard::Nebula),
+            SectorType::AsteroidField => Some(Hazard::Asteroids),
+            SectorType::Anomaly => Some(Hazard::Anomaly),
+            SectorType::DarkRift => Some(Hazard::DarkRift),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hazard::Nebula => "Nebula",
+            Hazard::Asteroids => "Asteroids",
+            Hazard::Anomaly => "Anomaly",
+            Hazard::DarkRift => "Dark Rift",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Hazard::Nebula => "sensors jammed, shield regen halved",
+            Hazard::Asteroids => "periodic hull strikes",
+            Hazard::Anomaly => "system power surges",
+            Hazard::DarkRift => "crew health draining",
+        }
+    }
+}
+
+// Environmental effects of the current sector. Nebulas work all the time;
+// the other hazards only bite while an event or fight keeps the ship there.
+#[derive(Resource, Default)]
+pub struct Environment {
+    pub hazard: Option<Hazard>,
+    timer: f32,
+}
+
+impl Environment {
+    pub fn sensors_jammed(&self) -> bool {
+        self.hazard == Some(Hazard::Nebula)
+    }
+
+    pub fn shield_regen_multiplier(&self) -> f32 {
+        if self.hazard == Some(Hazard::Nebula) { 0.5 } else { 1.0 }
+    }
+}
+
+// Hazards only tick while something keeps the ship in the sector
+fn hazard_active(active_event: &ActiveEvent, active_combat: &ActiveCombat) -> bool {
+    active_event.event.is_some() || active_combat.is_active()
+}
+
+fn update_environment(
+    mut environment: ResMut<Environment>,
+    sector_map: Res<SectorMap>,
+) {
+    let hazard = sector_map.sectors.get(&sector_map.current_sector_id)
+        .and_then(|sector| Hazard::from_sector_type(&sector.sector_type));
+    if environment.hazard != hazard {
+        environment.hazard = hazard;
+        environment.timer = 0.0;
+        if let Some(hazard) = hazard {
+            println!("Warning: {} - {}.", hazard.name(), hazard.description());
+        }
+    }
+}
+
+fn strike_asteroids(
+    mut environment: ResMut<Environment>,
+    player_query: Query<(Entity, &Ship), With<PlayerShip>>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+    mut damage_writer: MessageWriter<ShipDamage>,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+) {
+    if environment.hazard != Some(Hazard::Asteroids) || !hazard_active(&active_event, &active_combat) {
+        return;
+    }
+
+    environment.timer += time.delta_secs();
+    if environment.timer < ASTEROID_INTERVAL {
+        return;
+    }
+    environment.timer = 0.0;
+
+    let Ok((player_entity, ship)) = player_query.single() else { return; };
+    let rng = &mut run_rng.environment;
+    // Good engines steer around most of the rocks
+    if rng.gen_bool(evasion(ship).min(1.0)) {
+        println!("You steer clear of a tumbling asteroid.");
+        return;
+    }
+
+    println!("An asteroid slams into the hull!");
+    damage_writer.write(ShipDamage {
+        target: player_entity,
+        amount: ASTEROID_DAMAGE,
+        system: Some(SystemKind::ALL[rng.gen_range(0..SystemKind::ALL.len())]),
+        // Shield layers stop rocks the same way they stop laser bolts
+        weapon: WeaponType::Laser,
+    });
+}
+
+// Surges bounce a bar of power from one system into another
+fn scramble_power(
+    mut environment: ResMut<Environment>,
+    mut player_query: Query<&mut Ship, With<PlayerShip>>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
+) {
+    if environment.hazard != Some(Hazard::Anomaly) || !hazard_active(&active_event, &active_combat) {
+        return;
+    }
+
+    environment.timer += time.delta_secs();
+    if environment.timer < ANOMALY_INTERVAL {
+        return;
+    }
+    environment.timer = 0.0;
+
+    let Ok(mut ship) = player_query.single_mut() else { return; };
+    let rng = &mut run_rng.environment;
+    let powered: Vec<SystemKind> = SystemKind::ALL.into_iter()
+        .filter(|system| ship.systems.module(*system).power_allocated > 0)
+        .collect();
+    let Some(&from) = powered.get(rng.gen_range(0..powered.len().max(1))) else { return; };
+
+    let receivers: Vec<SystemKind> = SystemKind::ALL.into_iter()
+        .filter(|system| *system != from)
+        .filter(|system| {
+            let module = ship.systems.module(*system);
+            module.power_allocated < module.capacity()
+        })
+        .collect();
+
+    ship.systems.module_mut(from).power_allocated -= 1;
+    // With nowhere to go, the power just drains back into the reactor
+    match receivers.get(rng.gen_range(0..receivers.len().max(1))) {
+        Some(&to) => {
+            ship.systems.module_mut(to).power_allocated += 1;
+            println!("An anomaly surge shunts power from {} to {}!", from.name(), to.name());
+        }
+        None => println!("An anomaly surge knocks power out of the {}!", from.name()),
+    }
+}
+
+fn drain_crew_in_rift(
+    environment: Res<Environment>,
+    mut crew: ResMut<CrewRoster>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+    time: Res<Time>,
+) {
+    if environment.hazard != Some(Hazard::DarkRift) || !hazard_active(&active_event, &active_combat) {
+        return;
+    }
+
+    let drain = RIFT_CREW_DRAIN * time.delta_secs();
+    for member in &mut crew.members {
+        member.health = (member.health - drain).max(0.0);
+    }
+    crew.remove_dead("was consumed by the Dark Rift");
+}
@@ -0,0 +1,597 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::crew::Skill;
+use crate::events::{EventChoice, EventOutcome, EventRequirement, GameEvent, GameEventType, RandomBranch};
+use crate::factions::{Faction, FactionRelations, RelationLevel, ShipClass};
+use crate::sector::{EventType, Sector, SectorEvent, SectorType};
+use crate::ship::{SystemKind, WeaponType};
+
+pub struct EventLibraryPlugin;
+
+impl Plugin for EventLibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EventLibrary::load_or_report(&event_directory()));
+    }
+}
+
+pub const EVENT_DIRECTORY: &str = "assets/events";
+
+// Resolves the event directory next to the crate when run through cargo
+pub fn event_directory() -> PathBuf {
+    match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(root) => PathBuf::from(root).join(EVENT_DIRECTORY),
+        Err(_) => PathBuf::from(EVENT_DIRECTORY),
+    }
+}
+
+// A number in an event file, either fixed or scaled by the sector's danger level
+#[derive(Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum Amount {
+    Fixed(f32),
+    Scaled { base: f32, per_danger: f32 },
+}
+
+impl Default for Amount {
+    fn default() -> Self {
+        Amount::Fixed(0.0)
+    }
+}
+
+impl Amount {
+    pub fn resolve(&self, danger_level: u32) -> f32 {
+        match self {
+            Amount::Fixed(value) => *value,
+            Amount::Scaled { base, per_danger } => base + per_danger * danger_level as f32,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EventDefinition {
+    pub id: String,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    // Sector types this event can appear in (empty means any)
+    #[serde(default)]
+    pub sector_types: Vec<SectorType>,
+    #[serde(default)]
+    pub min_danger: u32,
+    #[serde(default)]
+    pub max_danger: Option<u32>,
+    // Candidate factions for the event (empty keeps the sector event's faction)
+    #[serde(default)]
+    pub factions: Vec<Faction>,
+    // Faction used when a sector event carries none
+    #[serde(default)]
+    pub default_faction: Option<Faction>,
+    // Player standings with the faction this event is written for (empty means any)
+    #[serde(default)]
+    pub standings: Vec<RelationLevel>,
+    // Predefined sector events this definition answers
+    #[serde(default)]
+    pub sector_events: Vec<EventType>,
+    // Whether it can roll in sectors without a predefined event (defaults to
+    // true only for definitions that answer no sector events)
+    #[serde(default)]
+    pub random: Option<bool>,
+    pub event_type: GameEventType,
+    pub title: String,
+    pub description: String,
+    pub choices: Vec<ChoiceDefinition>,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ChoiceDefinition {
+    pub text: String,
+    pub outcome: OutcomeDefinition,
+    #[serde(default)]
+    pub requirements: Vec<RequirementDefinition>,
+}
+
+// Faction fields left empty refer to the event's own faction
+#[derive(Deserialize, Clone)]
+pub enum OutcomeDefinition {
+    Combat {
+        #[serde(default)]
+        enemy_faction: Option<Faction>,
+        #[serde(default)]
+        difficulty: Amount,
+        #[serde(default)]
+        ship_class: Option<ShipClass>,
+    },
+    Reward {
+        #[serde(default)]
+        scrap: Amount,
+        #[serde(default)]
+        fuel: Amount,
+        #[serde(default)]
+        crew: Option<String>,
+        #[serde(default)]
+        missiles: Amount,
+    },
+    Loss {
+        #[serde(default)]
+        scrap: Amount,
+        #[serde(default)]
+        fuel: Amount,
+        #[serde(default)]
+        hull_damage: Amount,
+    },
+    FactionChange {
+        #[serde(default)]
+        faction: Option<Faction>,
+        change: i32,
+    },
+    Discovery { item: String, description: String },
+    SystemDamage { system: SystemKind, amount: Amount },
+    GainWeapon(WeaponType),
+    Continue,
+    Chain(Vec<OutcomeDefinition>),
+    Random(Vec<BranchDefinition>),
+    // Id of the event shown next, usually one marked `random: false`
+    FollowUp(String),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BranchDefinition {
+    pub chance: f32,
+    #[serde(default)]
+    pub text: Option<String>,
+    pub outcome: OutcomeDefinition,
+}
+
+impl OutcomeDefinition {
+    // Ids of every follow-up this outcome can lead to
+    fn follow_ups(&self) -> Vec<&str> {
+        match self {
+            OutcomeDefinition::Chain(outcomes) => outcomes.iter().flat_map(|outcome| outcome.follow_ups()).collect(),
+            OutcomeDefinition::Random(branches) => branches.iter().flat_map(|branch| branch.outcome.follow_ups()).collect(),
+            OutcomeDefinition::FollowUp(id) => vec![id.as_str()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub enum RequirementDefinition {
+    Fuel(Amount),
+    Scrap(Amount),
+    CrewSkill { skill: Skill, level: u32 },
+}
+
+#[derive(Debug)]
+pub enum EventLoadError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, line: usize, column: usize, message: String },
+    DuplicateId { path: PathBuf, id: String },
+    UnknownFollowUp { id: String, follow_up: String },
+    FollowUpCycle { id: String },
+}
+
+impl fmt::Display for EventLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventLoadError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            EventLoadError::Parse { path, line, column, message } => {
+                write!(f, "{}:{}:{}: {}", path.display(), line, column, message)
+            }
+            EventLoadError::DuplicateId { path, id } => {
+                write!(f, "{}: event id \"{}\" is already defined", path.display(), id)
+            }
+            EventLoadError::UnknownFollowUp { id, follow_up } => {
+                write!(f, "event \"{}\" follows up with unknown event \"{}\"", id, follow_up)
+            }
+            EventLoadError::FollowUpCycle { id } => {
+                write!(f, "event \"{}\" leads back to itself through its follow-ups", id)
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct EventLibrary {
+    pub definitions: Vec<EventDefinition>,
+}
+
+impl EventLibrary {
+    // Loads every *.ron file in the directory, in file name order so seeded runs stay stable.
+    // Broken files and definitions are left out and returned as errors next to
+    // everything that did load; only an unreadable directory fails the load.
+    pub fn load(directory: &Path) -> Result<(Self, Vec<EventLoadError>), EventLoadError> {
+        let entries = fs::read_dir(directory).map_err(|error| {
+            EventLoadError::Io { path: directory.to_path_buf(), error }
+        })?;
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        let mut definitions = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            match load_file(&path) {
+                Ok(file_definitions) => {
+                    for definition in file_definitions {
+                        if definitions.iter().any(|existing: &EventDefinition| existing.id == definition.id) {
+                            errors.push(EventLoadError::DuplicateId { path: path.clone(), id: definition.id });
+                            continue;
+                        }
+                        definitions.push(definition);
+                    }
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let mut library = Self { definitions };
+        errors.extend(library.remove_broken_follow_ups());
+        Ok((library, errors))
+    }
+
+    // Follow-ups are expanded when an event is rolled, so every id must exist
+    // and no chain may loop back on itself. Definitions that break either rule
+    // are dropped, again and again until nothing left leads to a dropped one.
+    fn remove_broken_follow_ups(&mut self) -> Vec<EventLoadError> {
+        let mut errors = Vec::new();
+        loop {
+            let broken: Vec<(String, EventLoadError)> = self.definitions.iter()
+                .filter_map(|definition| {
+                    let id = definition.id.clone();
+                    if let Some(follow_up) = definition.follow_ups().into_iter().find(|follow_up| self.definition(follow_up).is_none()) {
+                        let error = EventLoadError::UnknownFollowUp { id: id.clone(), follow_up: follow_up.to_string() };
+                        return Some((id, error));
+                    }
+                    if self.reaches(&id, &id, &mut HashSet::new()) {
+                        return Some((id.clone(), EventLoadError::FollowUpCycle { id }));
+                    }
+                    None
+                })
+                .collect();
+            if broken.is_empty() {
+                return errors;
+            }
+
+            self.definitions.retain(|definition| broken.iter().all(|(id, _)| *id != definition.id));
+            errors.extend(broken.into_iter().map(|(_, error)| error));
+        }
+    }
+
+    fn reaches<'a>(&'a self, from: &'a str, target: &str, visited: &mut HashSet<&'a str>) -> bool {
+        let Some(definition) = self.definition(from) else { return false; };
+        for follow_up in definition.follow_ups() {
+            if follow_up == target {
+                return true;
+            }
+            if visited.insert(follow_up) && self.reaches(follow_up, target, visited) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn definition(&self, id: &str) -> Option<&EventDefinition> {
+        self.definitions.iter().find(|definition| definition.id == id)
+    }
+
+    // Reports and leaves out whatever is broken, but refuses to start a game
+    // that would have no events at all
+    pub fn load_or_report(directory: &Path) -> Self {
+        let (library, errors) = match Self::load(directory) {
+            Ok(loaded) => loaded,
+            Err(error) => panic!("Event load error: {}", error),
+        };
+        for error in &errors {
+            eprintln!("Event load error: {} - left out", error);
+        }
+        if library.definitions.is_empty() {
+            panic!("No usable event definitions in {}", directory.display());
+        }
+
+        println!("Loaded {} event definitions from {}", library.definitions.len(), directory.display());
+        library
+    }
+
+    // Picks an event for a sector, answering one of its predefined events if it has any
+    pub fn pick_event(
+        &self,
+        sector: &Sector,
+        relations: &FactionRelations,
+        rng: &mut impl Rng,
+    ) -> Option<GameEvent> {
+        let sector_event = if sector.events.is_empty() {
+            None
+        } else {
+            Some(&sector.events[rng.gen_range(0..sector.events.len())])
+        };
+
+        let candidates = self.candidates(sector, sector_event, relations);
+        let total: f32 = candidates.iter().map(|(_, _, weight)| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0.0..total);
+        for (definition, faction, weight) in &candidates {
+            if roll < *weight {
+                return Some(definition.instantiate(self, sector, sector_event, faction.clone()));
+            }
+            roll -= weight;
+        }
+        candidates.last().map(|(definition, faction, _)| {
+            definition.instantiate(self, sector, sector_event, faction.clone())
+        })
+    }
+
+    // Every (definition, faction) pairing that fits the sector, with its share of the weight
+    fn candidates(
+        &self,
+        sector: &Sector,
+        sector_event: Option<&SectorEvent>,
+        relations: &FactionRelations,
+    ) -> Vec<(&EventDefinition, Option<Faction>, f32)> {
+        let mut candidates = Vec::new();
+
+        for definition in &self.definitions {
+            if !definition.fits_sector(sector, sector_event) {
+                continue;
+            }
+
+            // A sector event's own faction wins over the definition's candidates
+            let event_faction = sector_event.and_then(|event| event.faction.clone());
+            let factions: Vec<Option<Faction>> = match event_faction {
+                Some(faction) if definition.factions.is_empty() || definition.factions.contains(&faction) => {
+                    vec![Some(faction)]
+                }
+                Some(_) => continue,
+                None if definition.factions.is_empty() => vec![definition.default_faction.clone()],
+                None => definition.factions.iter().cloned().map(Some).collect(),
+            };
+
+            let share = definition.weight / factions.len() as f32;
+            for faction in factions {
+                if definition.fits_standing(faction.as_ref(), relations) {
+                    candidates.push((definition, faction, share));
+                }
+            }
+        }
+
+        candidates
+    }
+}
+
+impl EventDefinition {
+    fn fits_sector(&self, sector: &Sector, sector_event: Option<&SectorEvent>) -> bool {
+        match sector_event {
+            Some(event) => {
+                if !self.sector_events.contains(&event.event_type) {
+                    return false;
+                }
+            }
+            None => {
+                if !self.random.unwrap_or(self.sector_events.is_empty()) {
+                    return false;
+                }
+            }
+        }
+
+        if !self.sector_types.is_empty() && !self.sector_types.contains(&sector.sector_type) {
+            return false;
+        }
+
+        sector.danger_level >= self.min_danger
+            && self.max_danger.is_none_or(|max| sector.danger_level <= max)
+    }
+
+    fn fits_standing(&self, faction: Option<&Faction>, relations: &FactionRelations) -> bool {
+        if self.standings.is_empty() {
+            return true;
+        }
+        match faction {
+            Some(faction) => self.standings.contains(&relations.standing(faction)),
+            None => false,
+        }
+    }
+
+    fn follow_ups(&self) -> Vec<&str> {
+        self.choices.iter().flat_map(|choice| choice.outcome.follow_ups()).collect()
+    }
+
+    // Follow-up events are built up front with the same sector and faction,
+    // so a running event never needs the library again
+    pub fn instantiate(
+        &self,
+        library: &EventLibrary,
+        sector: &Sector,
+        sector_event: Option<&SectorEvent>,
+        faction: Option<Faction>,
+    ) -> GameEvent {
+        let danger = sector.danger_level;
+        let fill = |text: &str| fill_text(text, faction.as_ref(), sector_event);
+
+        GameEvent {
+            _event_type: self.event_type.clone(),
+            title: fill(&self.title),
+            description: fill(&self.description),
+            choices: self.choices.iter()
+                .map(|choice| EventChoice {
+                    text: fill(&choice.text),
+                    outcome: choice.outcome.resolve(library, sector, sector_event, faction.as_ref()),
+                    requirements: choice.requirements.iter()
+                        .map(|requirement| requirement.resolve(danger))
+                        .collect(),
+                })
+                .collect(),
+            _faction: faction,
+        }
+    }
+}
+
+fn fill_text(text: &str, faction: Option<&Faction>, sector_event: Option<&SectorEvent>) -> String {
+    let faction_name = faction.map(|faction| faction.name()).unwrap_or("Unknown");
+    let details = sector_event.map(|event| event.description.as_str()).unwrap_or("");
+    text.replace("{faction}", faction_name).replace("{details}", details)
+}
+
+impl OutcomeDefinition {
+    fn resolve(
+        &self,
+        library: &EventLibrary,
+        sector: &Sector,
+        sector_event: Option<&SectorEvent>,
+        event_faction: Option<&Faction>,
+    ) -> EventOutcome {
+        let danger = sector.danger_level;
+        let faction_or_event = |faction: &Option<Faction>| {
+            faction.clone()
+                .or_else(|| event_faction.cloned())
+                .unwrap_or(Faction::Neutral)
+        };
+
+        match self {
+            OutcomeDefinition::Combat { enemy_faction, difficulty, ship_class } => EventOutcome::Combat {
+                enemy_faction: faction_or_event(enemy_faction),
+                difficulty: difficulty.resolve(danger).max(0.0) as u32,
+                // Fighting the sector's own ship means fighting the class it was rolled with
+                ship_class: ship_class.or_else(|| {
+                    enemy_faction.is_none()
+                        .then(|| sector_event.and_then(|event| event.ship_class))
+                        .flatten()
+                }),
+            },
+            OutcomeDefinition::Reward { scrap, fuel, crew, missiles } => EventOutcome::Reward {
+                scrap: scrap.resolve(danger) as i32,
+                fuel: fuel.resolve(danger),
+                crew: crew.clone(),
+                missiles: missiles.resolve(danger).max(0.0) as u32,
+            },
+            OutcomeDefinition::Loss { scrap, fuel, hull_damage } => EventOutcome::Loss {
+                scrap: scrap.resolve(danger) as i32,
+                fuel: fuel.resolve(danger),
+                hull_damage: hull_damage.resolve(danger),
+            },
+            OutcomeDefinition::FactionChange { faction, change } => EventOutcome::FactionChange {
+                faction: faction_or_event(faction),
+                change: *change,
+            },
+            OutcomeDefinition::Discovery { item, description } => EventOutcome::Discovery {
+                item: item.clone(),
+                description: description.clone(),
+            },
+            OutcomeDefinition::SystemDamage { system, amount } => EventOutcome::SystemDamage {
+                system: *system,
+                amount: amount.resolve(danger).max(0.0),
+            },
+            OutcomeDefinition::GainWeapon(weapon_type) => EventOutcome::GainWeapon(*weapon_type),
+            OutcomeDefinition::Continue => EventOutcome::Continue,
+            OutcomeDefinition::Chain(outcomes) => EventOutcome::Chain(
+                outcomes.iter()
+                    .map(|outcome| outcome.resolve(library, sector, sector_event, event_faction))
+                    .collect(),
+            ),
+            OutcomeDefinition::Random(branches) => EventOutcome::Random(
+                branches.iter()
+                    .map(|branch| RandomBranch {
+                        chance: branch.chance,
+                        text: branch.text.as_deref()
+                            .map(|text| fill_text(text, event_faction, sector_event)),
+                        outcome: branch.outcome.resolve(library, sector, sector_event, event_faction),
+                    })
+                    .collect(),
+            ),
+            // Load validation guarantees the id exists and the chain ends
+            OutcomeDefinition::FollowUp(id) => match library.definition(id) {
+                Some(definition) => EventOutcome::FollowUp(Box::new(
+                    definition.instantiate(library, sector, sector_event, event_faction.cloned()),
+                )),
+                None => EventOutcome::Continue,
+            },
+        }
+    }
+}
+
+impl RequirementDefinition {
+    fn resolve(&self, danger: u32) -> EventRequirement {
+        match self {
+            RequirementDefinition::Fuel(amount) => EventRequirement::Fuel(amount.resolve(danger)),
+            RequirementDefinition::Scrap(amount) => {
+                EventRequirement::Scrap(amount.resolve(danger).max(0.0) as u32)
+            }
+            RequirementDefinition::CrewSkill { skill, level } => EventRequirement::CrewSkill {
+                skill: *skill,
+                level: *level,
+            },
+        }
+    }
+}
+
+fn load_file(path: &Path) -> Result<Vec<EventDefinition>, EventLoadError> {
+    let contents = fs::read_to_string(path).map_err(|error| EventLoadError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    ron::from_str(&contents).map_err(|error| EventLoadError::Parse {
+        path: path.to_path_buf(),
+        line: error.span.start.line,
+        column: error.span.start.col,
+        message: error.code.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOOD_EVENTS: &str = r#"[
+        (id: "kept", event_type: Story, title: "Kept", description: "",
+            choices: [(text: "On", outcome: FollowUp("next"))]),
+        (id: "next", random: Some(false), event_type: Story, title: "Next", description: "",
+            choices: [(text: "Done", outcome: Continue)]),
+        (id: "dangling", event_type: Story, title: "Dangling", description: "",
+            choices: [(text: "On", outcome: FollowUp("missing"))]),
+        (id: "leads_to_dangling", event_type: Story, title: "Chained", description: "",
+            choices: [(text: "On", outcome: FollowUp("dangling"))]),
+    ]"#;
+
+    fn write_events(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("star-drifter-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        for (file, contents) in files {
+            fs::write(directory.join(file), contents).unwrap();
+        }
+        directory
+    }
+
+    #[test]
+    fn broken_files_and_definitions_are_left_out() {
+        let directory = write_events("partial", &[("a.ron", GOOD_EVENTS), ("b.ron", "[ (id: ")]);
+        let (library, errors) = EventLibrary::load(&directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let ids: Vec<&str> = library.definitions.iter().map(|definition| definition.id.as_str()).collect();
+        assert_eq!(ids, vec!["kept", "next"]);
+        assert!(errors.iter().any(|error| matches!(error, EventLoadError::Parse { .. })));
+        assert!(errors.iter().any(|error| matches!(error, EventLoadError::UnknownFollowUp { id, .. } if id == "dangling")));
+        assert!(errors.iter().any(|error| matches!(error, EventLoadError::UnknownFollowUp { id, .. } if id == "leads_to_dangling")));
+    }
+
+    #[test]
+    fn shipped_events_load_cleanly() {
+        let (library, errors) = EventLibrary::load(&event_directory()).unwrap();
+        assert!(errors.is_empty());
+        assert!(!library.definitions.is_empty());
+    }
+}
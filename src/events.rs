@@ -1,7 +1,15 @@
 use bevy::prelude::*;
 use rand::Rng;
-use crate::factions::Faction;
-use crate::game::GameData;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use crate::factions::{Faction, FactionRelations, ReputationChange, ShipClass};
+use crate::event_library::EventLibrary;
+use crate::game::{GameData, GameState, RunStats};
+use crate::combat::StartCombat;
+use crate::ship::{PlayerShip, Ship, SystemKind, Weapon, WeaponType};
+use crate::crew::{CrewRoster, Skill};
+use crate::rng::RunRng;
+use crate::actions::{Action, Actions};
 
 pub struct EventsPlugin;
 
@@ -13,17 +21,17 @@ impl Plugin for EventsPlugin {
         app
             .add_message::<GameEvent>()
             .insert_resource(ActiveEvent::default())
-            .insert_resource(InputConsumed::default())
-            .configure_sets(Update, EventSystemSet.before(crate::sector::NavigationSystemSet))
+            .configure_sets(Update, EventSystemSet
+                .before(crate::sector::NavigationSystemSet)
+                .run_if(in_state(GameState::Playing)))
             .add_systems(Update, (
                 handle_game_events,
                 process_event_choices,
-            ).in_set(EventSystemSet))
-            .add_systems(Update, clear_consumed_input.after(crate::sector::NavigationSystemSet));
+            ).in_set(EventSystemSet));
     }
 }
 
-#[derive(Message, Clone)]
+#[derive(Message, Clone, Serialize, Deserialize)]
 pub struct GameEvent {
     pub _event_type: GameEventType,
     pub title: String,
@@ -32,7 +40,7 @@ pub struct GameEvent {
     pub _faction: Option<Faction>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum GameEventType {
     Combat,
     Diplomacy,
@@ -43,46 +51,71 @@ pub enum GameEventType {
     Anomaly,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EventChoice {
     pub text: String,
     pub outcome: EventOutcome,
     pub requirements: Vec<EventRequirement>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum EventOutcome {
-    Combat { enemy_faction: Faction, difficulty: u32 },
-    Reward { scrap: i32, fuel: f32, crew: Option<String> },
+    Combat {
+        enemy_faction: Faction,
+        difficulty: u32,
+        // Rolled when the fight starts if the event doesn't name one
+        #[serde(default)]
+        ship_class: Option<ShipClass>,
+    },
+    Reward {
+        scrap: i32,
+        fuel: f32,
+        crew: Option<String>,
+        #[serde(default)]
+        missiles: u32,
+    },
     Loss { scrap: i32, fuel: f32, hull_damage: f32 },
     FactionChange { faction: Faction, change: i32 },
     Discovery { item: String, description: String },
+    SystemDamage { system: SystemKind, amount: f32 },
+    GainWeapon(WeaponType),
     Continue,
+    // Applies every effect in order
+    Chain(Vec<EventOutcome>),
+    // Rolls one branch, weighted by each branch's chance
+    Random(Vec<RandomBranch>),
+    // Opens another event screen once this choice resolves
+    FollowUp(Box<GameEvent>),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RandomBranch {
+    pub chance: f32,
+    pub text: Option<String>,
+    pub outcome: EventOutcome,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum EventRequirement {
     Fuel(f32),
     Scrap(u32),
-    CrewSkill { _skill_type: String, _level: u32 },
+    CrewSkill { skill: Skill, level: u32 },
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct ActiveEvent {
     pub event: Option<GameEvent>,
 }
 
-#[derive(Resource, Default)]
-pub struct InputConsumed {
-    pub keys: Vec<KeyCode>,
-}
-
 // Public function to trigger event for a sector (called automatically when arriving)
 pub fn trigger_event_for_sector(
     sector_map: &crate::sector::SectorMap,
     sector_id: u32,
     event_writer: &mut MessageWriter<GameEvent>,
     active_event: &mut ActiveEvent,
+    relations: &FactionRelations,
+    library: &EventLibrary,
+    rng: &mut impl Rng,
 ) {
     // Only trigger if no event is currently active
     if active_event.event.is_some() {
@@ -90,19 +123,12 @@ pub fn trigger_event_for_sector(
     }
     
     if let Some(sector) = sector_map.sectors.get(&sector_id) {
-        if !sector.events.is_empty() {
-            let mut rng = rand::thread_rng();
-            let event_index = rng.gen_range(0..sector.events.len());
-            let sector_event = &sector.events[event_index];
-            
-            let game_event = create_game_event_from_sector_event(sector_event, sector.danger_level);
-            active_event.event = Some(game_event.clone());
-            event_writer.write(game_event);
-        } else {
-            // Generate random encounter if sector has no predefined events
-            let random_event = generate_random_event(sector.danger_level);
-            active_event.event = Some(random_event.clone());
-            event_writer.write(random_event);
+        match library.pick_event(sector, relations, rng) {
+            Some(game_event) => {
+                active_event.event = Some(game_event.clone());
+                event_writer.write(game_event);
+            }
+            None => println!("No event definition fits {} ({:?})", sector.name, sector.sector_type),
         }
     }
 }
@@ -117,438 +143,6 @@ fn _trigger_sector_events(
     // Disabled - events now trigger automatically when arriving at sectors
 }
 
-fn create_game_event_from_sector_event(
-    sector_event: &crate::sector::SectorEvent,
-    danger_level: u32,
-) -> GameEvent {
-    match sector_event.event_type {
-        crate::sector::EventType::Encounter => {
-            let faction = sector_event.faction.clone().unwrap_or(Faction::Spirats);
-            GameEvent {
-                _event_type: GameEventType::Combat,
-                title: format!("{} Encounter", faction.name()),
-                description: sector_event.description.clone(),
-                choices: vec![
-                    EventChoice {
-                        text: "Engage in combat".to_string(),
-                        outcome: EventOutcome::Combat { 
-                            enemy_faction: faction.clone(), 
-                            difficulty: danger_level 
-                        },
-                        requirements: vec![],
-                    },
-                    EventChoice {
-                        text: "Attempt to negotiate".to_string(),
-                        outcome: EventOutcome::FactionChange { 
-                            faction: faction.clone(), 
-                            change: 1 
-                        },
-                        requirements: vec![
-                            EventRequirement::CrewSkill { 
-                                _skill_type: "diplomacy".to_string(), 
-                                _level: 2 
-                            }
-                        ],
-                    },
-                    EventChoice {
-                        text: "Try to escape".to_string(),
-                        outcome: EventOutcome::Loss { 
-                            scrap: 0, 
-                            fuel: 1.0, 
-                            hull_damage: 0.0 
-                        },
-                        requirements: vec![
-                            EventRequirement::Fuel(2.0),
-                        ],
-                    },
-                    EventChoice {
-                        text: "Ignore and continue".to_string(),
-                        outcome: EventOutcome::Continue,
-                        requirements: vec![],
-                    },
-                ],
-                _faction: Some(faction),
-            }
-        }
-        crate::sector::EventType::Discovery => {
-            GameEvent {
-                _event_type: GameEventType::Discovery,
-                title: "Discovery".to_string(),
-                description: sector_event.description.clone(),
-                choices: vec![
-                    EventChoice {
-                        text: "Investigate carefully".to_string(),
-                        outcome: EventOutcome::Reward { 
-                            scrap: 10 + (danger_level as i32 * 5), 
-                            fuel: 0.0, 
-                            crew: None 
-                        },
-                        requirements: vec![],
-                    },
-                    EventChoice {
-                        text: "Quick salvage and leave".to_string(),
-                        outcome: EventOutcome::Reward { 
-                            scrap: 5, 
-                            fuel: 0.0, 
-                            crew: None 
-                        },
-                        requirements: vec![],
-                    },
-                    EventChoice {
-                        text: "Ignore and continue".to_string(),
-                        outcome: EventOutcome::Continue,
-                        requirements: vec![],
-                    },
-                ],
-                _faction: sector_event.faction.clone(),
-            }
-        }
-        crate::sector::EventType::Opportunity => {
-            GameEvent {
-                _event_type: GameEventType::Diplomacy,
-                title: "Distress Call".to_string(),
-                description: sector_event.description.clone(),
-                choices: vec![
-                    EventChoice {
-                        text: "Offer assistance".to_string(),
-                        outcome: EventOutcome::Reward { 
-                            scrap: 0, 
-                            fuel: 2.0, 
-                            crew: Some("Grateful Survivor".to_string()) 
-                        },
-                        requirements: vec![
-                            EventRequirement::Scrap(5),
-                        ],
-                    },
-                    EventChoice {
-                        text: "Demand payment first".to_string(),
-                        outcome: EventOutcome::Reward { 
-                            scrap: 15, 
-                            fuel: 0.0, 
-                            crew: None 
-                        },
-                        requirements: vec![],
-                    },
-                    EventChoice {
-                        text: "Ignore the distress call".to_string(),
-                        outcome: EventOutcome::Continue,
-                        requirements: vec![],
-                    },
-                ],
-                _faction: None,
-            }
-        }
-        crate::sector::EventType::Hazard => {
-            GameEvent {
-                _event_type: GameEventType::Hazard,
-                title: "Space Hazard".to_string(),
-                description: sector_event.description.clone(),
-                choices: vec![
-                    EventChoice {
-                        text: "Navigate carefully".to_string(),
-                        outcome: EventOutcome::Loss { 
-                            scrap: 0, 
-                            fuel: 1.0, 
-                            hull_damage: 0.0 
-                        },
-                        requirements: vec![
-                            EventRequirement::CrewSkill { 
-                                _skill_type: "piloting".to_string(), 
-                                _level: 2 
-                            }
-                        ],
-                    },
-                    EventChoice {
-                        text: "Push through quickly".to_string(),
-                        outcome: EventOutcome::Loss { 
-                            scrap: 0, 
-                            fuel: 0.5, 
-                            hull_damage: 5.0 
-                        },
-                        requirements: vec![],
-                    },
-                    EventChoice {
-                        text: "Find alternate route".to_string(),
-                        outcome: EventOutcome::Loss { 
-                            scrap: 0, 
-                            fuel: 2.0, 
-                            hull_damage: 0.0 
-                        },
-                        requirements: vec![
-                            EventRequirement::Fuel(3.0),
-                        ],
-                    },
-                    EventChoice {
-                        text: "Avoid the hazard".to_string(),
-                        outcome: EventOutcome::Continue,
-                        requirements: vec![],
-                    },
-                ],
-                _faction: None,
-            }
-        }
-        crate::sector::EventType::Story => {
-            let faction = sector_event.faction.clone().unwrap_or(Faction::Celestials);
-            GameEvent {
-                _event_type: GameEventType::Story,
-                title: format!("{} Artifact", faction.name()),
-                description: sector_event.description.clone(),
-                choices: vec![
-                    EventChoice {
-                        text: "Study the ancient technology".to_string(),
-                        outcome: EventOutcome::Discovery { 
-                            item: "Ancient Knowledge".to_string(),
-                            description: "Your crew gains insight into advanced technologies.".to_string(),
-                        },
-                        requirements: vec![
-                            EventRequirement::CrewSkill { 
-                                _skill_type: "science".to_string(), 
-                                _level: 3 
-                            }
-                        ],
-                    },
-                    EventChoice {
-                        text: "Salvage what you can".to_string(),
-                        outcome: EventOutcome::Reward { 
-                            scrap: 20, 
-                            fuel: 0.0, 
-                            crew: None 
-                        },
-                        requirements: vec![],
-                    },
-                    EventChoice {
-                        text: "Leave it undisturbed".to_string(),
-                        outcome: EventOutcome::FactionChange { 
-                            faction: faction.clone(), 
-                            change: 2 
-                        },
-                        requirements: vec![],
-                    },
-                ],
-                _faction: Some(faction),
-            }
-        }
-    }
-}
-
-fn generate_random_event(danger_level: u32) -> GameEvent {
-    let mut rng = rand::thread_rng();
-    
-    match rng.gen_range(0..100) {
-        0..=30 => generate_merchant_event(),
-        31..=50 => generate_anomaly_event(danger_level),
-        51..=70 => generate_derelict_event(danger_level),
-        71..=85 => generate_pirate_event(danger_level),
-        _ => generate_faction_event(danger_level),
-    }
-}
-
-fn generate_merchant_event() -> GameEvent {
-    GameEvent {
-        _event_type: GameEventType::Trade,
-        title: "Traveling Merchant".to_string(),
-        description: "A merchant ship hails you, offering to trade supplies.".to_string(),
-        choices: vec![
-            EventChoice {
-                text: "Trade scrap for fuel".to_string(),
-                outcome: EventOutcome::Reward { 
-                    scrap: -10, 
-                    fuel: 3.0, 
-                    crew: None 
-                },
-                requirements: vec![EventRequirement::Scrap(10)],
-            },
-            EventChoice {
-                text: "Trade fuel for scrap".to_string(),
-                outcome: EventOutcome::Reward { 
-                    scrap: 15, 
-                    fuel: -2.0, 
-                    crew: None 
-                },
-                requirements: vec![EventRequirement::Fuel(2.0)],
-            },
-            EventChoice {
-                text: "Decline and continue".to_string(),
-                outcome: EventOutcome::Continue,
-                requirements: vec![],
-            },
-        ],
-        _faction: Some(Faction::Neutral),
-    }
-}
-
-fn generate_anomaly_event(danger_level: u32) -> GameEvent {
-    GameEvent {
-        _event_type: GameEventType::Anomaly,
-        title: "Cosmic Anomaly".to_string(),
-        description: "Your sensors detect a strange energy signature ahead.".to_string(),
-        choices: vec![
-            EventChoice {
-                text: "Investigate the anomaly".to_string(),
-                outcome: EventOutcome::Reward { 
-                    scrap: (danger_level as i32) * 8, 
-                    fuel: 0.0, 
-                    crew: None 
-                },
-                requirements: vec![],
-            },
-            EventChoice {
-                text: "Scan from a safe distance".to_string(),
-                outcome: EventOutcome::Reward { 
-                    scrap: (danger_level as i32) * 3, 
-                    fuel: 0.0, 
-                    crew: None 
-                },
-                requirements: vec![
-                    EventRequirement::CrewSkill { 
-                        _skill_type: "sensors".to_string(), 
-                        _level: 2 
-                    }
-                ],
-            },
-            EventChoice {
-                text: "Ignore and continue".to_string(),
-                outcome: EventOutcome::Continue,
-                requirements: vec![],
-            },
-        ],
-        _faction: None,
-    }
-}
-
-fn generate_derelict_event(danger_level: u32) -> GameEvent {
-    GameEvent {
-        _event_type: GameEventType::Discovery,
-        title: "Derelict Ship".to_string(),
-        description: "You discover the wreckage of an ancient vessel drifting in space.".to_string(),
-        choices: vec![
-            EventChoice {
-                text: "Board and explore".to_string(),
-                outcome: EventOutcome::Reward { 
-                    scrap: (danger_level as i32) * 6, 
-                    fuel: 1.0, 
-                    crew: None 
-                },
-                requirements: vec![],
-            },
-            EventChoice {
-                text: "Salvage from outside".to_string(),
-                outcome: EventOutcome::Reward { 
-                    scrap: (danger_level as i32) * 3, 
-                    fuel: 0.0, 
-                    crew: None 
-                },
-                requirements: vec![],
-            },
-            EventChoice {
-                text: "Leave it alone".to_string(),
-                outcome: EventOutcome::Continue,
-                requirements: vec![],
-            },
-        ],
-        _faction: None,
-    }
-}
-
-fn generate_pirate_event(danger_level: u32) -> GameEvent {
-    GameEvent {
-        _event_type: GameEventType::Combat,
-        title: "Spirat Raiders".to_string(),
-        description: "Spirat pirates emerge from an asteroid field, demanding tribute!".to_string(),
-        choices: vec![
-            EventChoice {
-                text: "Fight the pirates".to_string(),
-                outcome: EventOutcome::Combat { 
-                    enemy_faction: Faction::Spirats, 
-                    difficulty: danger_level + 1 
-                },
-                requirements: vec![],
-            },
-            EventChoice {
-                text: "Pay tribute".to_string(),
-                outcome: EventOutcome::Loss { 
-                    scrap: (danger_level as i32) * 5, 
-                    fuel: 0.0, 
-                    hull_damage: 0.0 
-                },
-                requirements: vec![EventRequirement::Scrap((danger_level * 5) as u32)],
-            },
-            EventChoice {
-                text: "Try to outrun them".to_string(),
-                outcome: EventOutcome::Loss { 
-                    scrap: 0, 
-                    fuel: 2.0, 
-                    hull_damage: 2.0 
-                },
-                requirements: vec![
-                    EventRequirement::Fuel(3.0),
-                    EventRequirement::CrewSkill { 
-                        _skill_type: "engines".to_string(), 
-                        _level: 2 
-                    }
-                ],
-            },
-            EventChoice {
-                text: "Ignore and continue".to_string(),
-                outcome: EventOutcome::Continue,
-                requirements: vec![],
-            },
-        ],
-        _faction: Some(Faction::Spirats),
-    }
-}
-
-fn generate_faction_event(danger_level: u32) -> GameEvent {
-    let mut rng = rand::thread_rng();
-    let faction = match rng.gen_range(0..6) {
-        0 => Faction::Cosmicons,
-        1 => Faction::Spirats,
-        2 => Faction::Webes,
-        3 => Faction::Celestials,
-        4 => Faction::Spades,
-        _ => Faction::Archs,
-    };
-
-    GameEvent {
-        _event_type: GameEventType::Diplomacy,
-        title: format!("{} Patrol", faction.name()),
-        description: format!("A {} patrol ship approaches your vessel.", faction.name()),
-        choices: vec![
-            EventChoice {
-                text: "Hail them peacefully".to_string(),
-                outcome: EventOutcome::FactionChange { 
-                    faction: faction.clone(), 
-                    change: 1 
-                },
-                requirements: vec![],
-            },
-            EventChoice {
-                text: "Prepare for combat".to_string(),
-                outcome: EventOutcome::Combat { 
-                    enemy_faction: faction.clone(), 
-                    difficulty: danger_level 
-                },
-                requirements: vec![],
-            },
-            EventChoice {
-                text: "Try to avoid them".to_string(),
-                outcome: EventOutcome::Loss { 
-                    scrap: 0, 
-                    fuel: 1.5, 
-                    hull_damage: 0.0 
-                },
-                requirements: vec![EventRequirement::Fuel(2.0)],
-            },
-            EventChoice {
-                text: "Ignore and continue".to_string(),
-                outcome: EventOutcome::Continue,
-                requirements: vec![],
-            },
-        ],
-        _faction: Some(faction),
-    }
-}
-
 fn handle_game_events(
     mut event_reader: MessageReader<GameEvent>,
     _active_event: ResMut<ActiveEvent>,
@@ -561,106 +155,278 @@ fn handle_game_events(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_event_choices(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
     mut active_event: ResMut<ActiveEvent>,
     mut game_data: ResMut<GameData>,
-    mut input_consumed: ResMut<InputConsumed>,
+    mut combat_writer: MessageWriter<StartCombat>,
+    mut player_query: Query<&mut Ship, With<PlayerShip>>,
+    mut run_stats: ResMut<RunStats>,
+    mut crew: ResMut<CrewRoster>,
+    mut reputation_writer: MessageWriter<ReputationChange>,
+    mut event_writer: MessageWriter<GameEvent>,
+    mut run_rng: ResMut<RunRng>,
+    active_combat: Res<crate::combat::ActiveCombat>,
 ) {
+    let Ok(mut player_ship) = player_query.single_mut() else { return; };
+
+    // A follow-up screen waits until any fight it started is over
+    if active_combat.is_active() {
+        return;
+    }
+
     if let Some(event) = &active_event.event {
-        let mut choice_selected = None;
-        let mut consumed_key = None;
-        
-        if keyboard.just_pressed(KeyCode::Digit1) {
-            choice_selected = Some(0);
-            consumed_key = Some(KeyCode::Digit1);
-        } else if keyboard.just_pressed(KeyCode::Digit2) {
-            choice_selected = Some(1);
-            consumed_key = Some(KeyCode::Digit2);
-        } else if keyboard.just_pressed(KeyCode::Digit3) {
-            choice_selected = Some(2);
-            consumed_key = Some(KeyCode::Digit3);
-        }
-        
-        if let Some(choice_idx) = choice_selected {
-            if let Some(key) = consumed_key {
-                input_consumed.keys.push(key);
-            }
-            
+        if let Some(choice_idx) = actions.index_of(Action::ChooseOption) {
             if choice_idx < event.choices.len() {
                 let choice = &event.choices[choice_idx];
                 
                 // Check requirements
-                let can_choose = check_requirements(&choice.requirements, &game_data);
+                let failures = requirement_failures(&choice.requirements, &game_data, &crew);
                 
-                if can_choose {
-                    apply_outcome(&choice.outcome, &mut game_data);
-                    active_event.event = None;
+                if failures.is_empty() {
+                    // Crew members who handled the choice learn from it
+                    for requirement in &choice.requirements {
+                        if let EventRequirement::CrewSkill { skill, .. } = requirement {
+                            crew.use_skill(*skill);
+                        }
+                    }
+
+                    let mut context = OutcomeContext {
+                        game_data: &mut game_data,
+                        player_ship: &mut player_ship,
+                        run_stats: &mut run_stats,
+                        crew: &mut crew,
+                        combat_writer: &mut combat_writer,
+                        reputation_writer: &mut reputation_writer,
+                        rng: &mut run_rng.events,
+                        event_title: &event.title,
+                    };
+                    let follow_up = apply_outcome(&choice.outcome, &mut context);
+
+                    if player_ship.is_destroyed() && run_stats.cause_of_death.is_none() {
+                        run_stats.cause_of_death = Some(format!("Hull breached during {}", event.title));
+                    }
+
+                    if let Some(next_event) = &follow_up {
+                        event_writer.write(next_event.clone());
+                    }
+                    active_event.event = follow_up;
                 } else {
-                    println!("Cannot choose this option - requirements not met!");
+                    println!("Cannot choose this option - {}", failures.join(", "));
                 }
             }
         }
     }
 }
 
-fn clear_consumed_input(mut input_consumed: ResMut<InputConsumed>) {
-    input_consumed.keys.clear();
-}
-
-fn check_requirements(requirements: &[EventRequirement], game_data: &GameData) -> bool {
+// Lists a reason for every requirement the player currently fails
+pub fn requirement_failures(
+    requirements: &[EventRequirement],
+    game_data: &GameData,
+    crew: &CrewRoster,
+) -> Vec<String> {
+    let mut failures = Vec::new();
     for requirement in requirements {
         match requirement {
             EventRequirement::Fuel(amount) => {
                 if game_data.fuel < *amount {
-                    return false;
+                    failures.push(format!("Requires {:.1} fuel", amount));
                 }
             }
             EventRequirement::Scrap(amount) => {
                 if game_data.scrap < *amount {
-                    return false;
+                    failures.push(format!("Requires {} scrap", amount));
                 }
             }
-            EventRequirement::CrewSkill { _skill_type: _, _level: _ } => {
-                // TODO: Implement crew skill checking
+            EventRequirement::CrewSkill { skill, level } => {
+                if crew.best_level(*skill) < *level {
+                    failures.push(format!("Requires {} {}", skill.name(), level));
+                }
             }
         }
     }
-    true
+    failures
 }
 
-fn apply_outcome(outcome: &EventOutcome, game_data: &mut GameData) {
+// Everything an outcome may touch while it resolves
+struct OutcomeContext<'a, 'c, 'r> {
+    game_data: &'a mut GameData,
+    player_ship: &'a mut Ship,
+    run_stats: &'a mut RunStats,
+    crew: &'a mut CrewRoster,
+    combat_writer: &'a mut MessageWriter<'c, StartCombat>,
+    reputation_writer: &'a mut MessageWriter<'r, ReputationChange>,
+    rng: &'a mut StdRng,
+    event_title: &'a str,
+}
+
+// Applies an outcome and returns the follow-up event it leads to, if any
+fn apply_outcome(outcome: &EventOutcome, context: &mut OutcomeContext) -> Option<GameEvent> {
     match outcome {
-        EventOutcome::Reward { scrap, fuel, crew } => {
-            game_data.scrap = (game_data.scrap as i32 + scrap).max(0) as u32;
-            game_data.fuel = (game_data.fuel + fuel).max(0.0);
-            if let Some(crew_name) = crew {
-                println!("New crew member joined: {}", crew_name);
-                // TODO: Add crew member to game data
+        EventOutcome::Reward { scrap, fuel, crew: crew_name, missiles } => {
+            context.game_data.scrap = (context.game_data.scrap as i32 + scrap).max(0) as u32;
+            context.game_data.fuel = (context.game_data.fuel + fuel).max(0.0);
+            context.run_stats.scrap_collected += (*scrap).max(0) as u32;
+            if *missiles > 0 {
+                context.player_ship.missiles += missiles;
+                println!("Recovered {} missiles.", missiles);
+            }
+            if let Some(crew_name) = crew_name {
+                context.crew.recruit(crew_name, context.rng);
             }
         }
         EventOutcome::Loss { scrap, fuel, hull_damage } => {
-            game_data.scrap = (game_data.scrap as i32 - scrap).max(0) as u32;
-            game_data.fuel = (game_data.fuel - fuel).max(0.0);
+            context.game_data.scrap = (context.game_data.scrap as i32 - scrap).max(0) as u32;
+            context.game_data.fuel = (context.game_data.fuel - fuel).max(0.0);
             if *hull_damage > 0.0 {
-                println!("Hull took {} damage!", hull_damage);
-                // TODO: Apply hull damage to ship
+                let ship = &mut *context.player_ship;
+                ship.hull = (ship.hull - hull_damage).max(0.0);
+                println!("Hull took {} damage! ({:.0}/{:.0})", hull_damage, ship.hull, ship.max_hull);
             }
         }
-        EventOutcome::Combat { enemy_faction, difficulty } => {
-            println!("Combat initiated with {} (difficulty: {})!", enemy_faction.name(), difficulty);
-            // TODO: Implement combat system
+        EventOutcome::Combat { enemy_faction, difficulty, ship_class } => {
+            context.combat_writer.write(StartCombat {
+                enemy_faction: enemy_faction.clone(),
+                difficulty: *difficulty,
+                ship_class: *ship_class,
+            });
         }
         EventOutcome::FactionChange { faction, change } => {
-            println!("Faction relation with {} changed by {}", faction.name(), change);
-            // TODO: Update faction relations
+            context.reputation_writer.write(ReputationChange {
+                faction: faction.clone(),
+                change: *change as f32,
+            });
         }
         EventOutcome::Discovery { item, description } => {
             println!("Discovery: {} - {}", item, description);
             // TODO: Add discovery to inventory/log
         }
+        EventOutcome::SystemDamage { system, amount } => {
+            let module = context.player_ship.systems.module_mut(*system);
+            module.take_damage(*amount);
+            println!("The {} system was damaged! ({:.0}% health)", system.name(), module.health);
+        }
+        EventOutcome::GainWeapon(weapon_type) => {
+            match context.player_ship.install_weapon(Weapon::new(*weapon_type)) {
+                Ok(()) => println!("Installed a salvaged {}.", weapon_type.name()),
+                Err(reason) => println!("Had to leave the {} behind: {}.", weapon_type.name(), reason),
+            }
+        }
         EventOutcome::Continue => {
             println!("You continue on your journey...");
         }
+        EventOutcome::Chain(outcomes) => {
+            // The last follow-up in the chain wins
+            let mut follow_up = None;
+            for outcome in outcomes {
+                if let Some(next_event) = apply_outcome(outcome, context) {
+                    follow_up = Some(next_event);
+                }
+            }
+            return follow_up;
+        }
+        EventOutcome::Random(branches) => {
+            if let Some(branch) = roll_branch(branches, context.rng) {
+                let follow_up = apply_outcome(&branch.outcome, context);
+                // What happened gets its own screen before anything the branch leads to
+                return match &branch.text {
+                    Some(text) => Some(result_screen(context.event_title, text, follow_up)),
+                    None => follow_up,
+                };
+            }
+        }
+        EventOutcome::FollowUp(next_event) => {
+            return Some((**next_event).clone());
+        }
     }
+    None
+}
+
+// A single-choice screen telling the player how a rolled branch turned out
+fn result_screen(title: &str, text: &str, follow_up: Option<GameEvent>) -> GameEvent {
+    let outcome = match follow_up {
+        Some(next_event) => EventOutcome::FollowUp(Box::new(next_event)),
+        None => EventOutcome::Continue,
+    };
+    GameEvent {
+        _event_type: GameEventType::Story,
+        title: title.to_string(),
+        description: text.to_string(),
+        choices: vec![EventChoice {
+            text: "Continue".to_string(),
+            outcome,
+            requirements: Vec::new(),
+        }],
+        _faction: None,
+    }
+}
+
+fn roll_branch<'a>(branches: &'a [RandomBranch], rng: &mut impl Rng) -> Option<&'a RandomBranch> {
+    let total: f32 = branches.iter().map(|branch| branch.chance.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut roll = rng.gen_range(0.0..total);
+    for branch in branches {
+        let chance = branch.chance.max(0.0);
+        if roll < chance {
+            return Some(branch);
+        }
+        roll -= chance;
+    }
+    branches.last()
+}
+
+// What a choice is known to lead to, shown next to it on the event screen.
+// None when it changes nothing or only opens another screen.
+pub fn outcome_preview(outcome: &EventOutcome) -> Option<String> {
+    let preview = match outcome {
+        EventOutcome::Reward { scrap, fuel, crew, missiles } => {
+            let mut parts = Vec::new();
+            if *scrap != 0 {
+                parts.push(format!("{:+} scrap", scrap));
+            }
+            if *fuel != 0.0 {
+                parts.push(format!("{:+.1} fuel", fuel));
+            }
+            if crew.is_some() {
+                parts.push("new crew member".to_string());
+            }
+            if *missiles > 0 {
+                parts.push(format!("+{} missiles", missiles));
+            }
+            parts.join(", ")
+        }
+        EventOutcome::Loss { scrap, fuel, hull_damage } => {
+            let mut parts = Vec::new();
+            if *scrap != 0 {
+                parts.push(format!("-{} scrap", scrap));
+            }
+            if *fuel != 0.0 {
+                parts.push(format!("-{:.1} fuel", fuel));
+            }
+            if *hull_damage > 0.0 {
+                parts.push(format!("{:.0} hull damage", hull_damage));
+            }
+            parts.join(", ")
+        }
+        EventOutcome::Combat { enemy_faction, ship_class, .. } => match ship_class {
+            Some(class) => format!("fight a {} {}", enemy_faction.name(), class.name()),
+            None => format!("fight the {}", enemy_faction.name()),
+        },
+        EventOutcome::FactionChange { faction, change } => format!("{} standing {:+}", faction.name(), change),
+        EventOutcome::Discovery { item, .. } => format!("find {}", item),
+        EventOutcome::SystemDamage { system, .. } => format!("damages the {}", system.name()),
+        EventOutcome::GainWeapon(weapon_type) => format!("gain a {}", weapon_type.name()),
+        EventOutcome::Chain(outcomes) => outcomes.iter()
+            .filter_map(outcome_preview)
+            .collect::<Vec<_>>()
+            .join(", "),
+        // The branch isn't rolled until the choice is made
+        EventOutcome::Random(_) => "outcome uncertain".to_string(),
+        EventOutcome::Continue | EventOutcome::FollowUp(_) => String::new(),
+    };
+    if preview.is_empty() { None } else { Some(preview) }
 }
@@ -1,14 +1,20 @@
 use bevy::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use crate::combat::{CombatEnded, CombatResult};
+use crate::game::{GameState, RunStart};
 
 pub struct FactionsPlugin;
 
 impl Plugin for FactionsPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, setup_factions)
-            .add_systems(Update, update_faction_relations);
+            .add_message::<ReputationChange>()
+            .add_systems(RunStart, setup_factions)
+            .add_systems(Update, (
+                penalize_combat_kills,
+                update_faction_relations,
+            ).chain().run_if(in_state(GameState::Playing)));
     }
 }
 
@@ -36,16 +42,69 @@ impl Faction {
         }
     }
 
-
-
+    pub const MAJOR: [Faction; 6] = [
+        Faction::Cosmicons,
+        Faction::Spirats,
+        Faction::Webes,
+        Faction::Celestials,
+        Faction::Spades,
+        Faction::Archs,
+    ];
 }
 
+// Player standing is clamped to this range on either side
+pub const MAX_REPUTATION: f32 = 10.0;
+// Standing lost with a faction for destroying one of its ships
+const KILL_REPUTATION_PENALTY: f32 = 3.0;
+
 #[derive(Resource)]
 pub struct FactionRelations {
-    _relations: std::collections::HashMap<(Faction, Faction), RelationLevel>,
+    relations: std::collections::HashMap<(Faction, Faction), RelationLevel>,
+    reputation: std::collections::HashMap<Faction, f32>,
+}
+
+impl FactionRelations {
+    pub fn relation(&self, from: &Faction, to: &Faction) -> Option<&RelationLevel> {
+        self.relations.get(&(from.clone(), to.clone()))
+    }
+
+    pub fn reputation(&self, faction: &Faction) -> f32 {
+        self.reputation.get(faction).copied().unwrap_or(0.0)
+    }
+
+    pub fn standing(&self, faction: &Faction) -> RelationLevel {
+        RelationLevel::from_reputation(self.reputation(faction))
+    }
+
+    // Applies a change to one faction and ripples it through their allies and enemies
+    pub fn change_reputation(&mut self, faction: &Faction, change: f32) {
+        self.adjust_reputation(faction, change);
+
+        for other in Faction::MAJOR.iter().filter(|other| *other != faction) {
+            let spread = self.relation(faction, other)
+                .map(|level| level.reputation_spread())
+                .unwrap_or(0.0);
+            if spread != 0.0 {
+                self.adjust_reputation(other, change * spread);
+            }
+        }
+    }
+
+    pub fn reputation_table(&self) -> &std::collections::HashMap<Faction, f32> {
+        &self.reputation
+    }
+
+    pub fn restore_reputation(&mut self, reputation: std::collections::HashMap<Faction, f32>) {
+        self.reputation = reputation;
+    }
+
+    fn adjust_reputation(&mut self, faction: &Faction, change: f32) {
+        let score = self.reputation.entry(faction.clone()).or_insert(0.0);
+        *score = (*score + change).clamp(-MAX_REPUTATION, MAX_REPUTATION);
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RelationLevel {
     Hostile,
     Unfriendly,
@@ -54,8 +113,41 @@ pub enum RelationLevel {
     Allied,
 }
 
+impl RelationLevel {
+    pub fn from_reputation(score: f32) -> Self {
+        if score <= -6.0 {
+            RelationLevel::Hostile
+        } else if score <= -2.0 {
+            RelationLevel::Unfriendly
+        } else if score < 2.0 {
+            RelationLevel::Neutral
+        } else if score < 6.0 {
+            RelationLevel::Friendly
+        } else {
+            RelationLevel::Allied
+        }
+    }
+
+    // Share of a reputation change passed on to a faction with this relation
+    fn reputation_spread(&self) -> f32 {
+        match self {
+            RelationLevel::Hostile => -0.5,
+            RelationLevel::Unfriendly => -0.25,
+            RelationLevel::Neutral => 0.0,
+            RelationLevel::Friendly => 0.5,
+            RelationLevel::Allied => 0.75,
+        }
+    }
+}
+
+#[derive(Message, Clone)]
+pub struct ReputationChange {
+    pub faction: Faction,
+    pub change: f32,
+}
+
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ShipClass {
     Scout,
     Fighter,
@@ -64,6 +156,29 @@ pub enum ShipClass {
     Flagship,
 }
 
+impl ShipClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShipClass::Scout => "Scout",
+            ShipClass::Fighter => "Fighter",
+            ShipClass::Cruiser => "Cruiser",
+            ShipClass::Battleship => "Battleship",
+            ShipClass::Flagship => "Flagship",
+        }
+    }
+
+    // Size of the hull, from 0 for a scout up to 4 for a flagship
+    pub fn tier(&self) -> u32 {
+        match self {
+            ShipClass::Scout => 0,
+            ShipClass::Fighter => 1,
+            ShipClass::Cruiser => 2,
+            ShipClass::Battleship => 3,
+            ShipClass::Flagship => 4,
+        }
+    }
+}
+
 fn setup_factions(mut commands: Commands) {
     let mut relations = std::collections::HashMap::new();
     
@@ -110,20 +225,56 @@ fn setup_factions(mut commands: Commands) {
     relations.insert((Faction::Archs, Faction::Celestials), RelationLevel::Hostile);
     relations.insert((Faction::Archs, Faction::Spades), RelationLevel::Allied);
 
-    commands.insert_resource(FactionRelations { _relations: relations });
+    // Starting player standing - pirates and the Archs distrust drifters
+    let mut reputation = std::collections::HashMap::new();
+    for faction in Faction::MAJOR {
+        reputation.insert(faction, 0.0);
+    }
+    reputation.insert(Faction::Spirats, -2.0);
+    reputation.insert(Faction::Archs, -6.0);
+
+    commands.insert_resource(FactionRelations { relations, reputation });
+}
+
+fn penalize_combat_kills(
+    mut ended_reader: MessageReader<CombatEnded>,
+    mut reputation_writer: MessageWriter<ReputationChange>,
+) {
+    for ended in ended_reader.read() {
+        if ended.result == CombatResult::Victory {
+            reputation_writer.write(ReputationChange {
+                faction: ended.enemy_faction.clone(),
+                change: -KILL_REPUTATION_PENALTY,
+            });
+        }
+    }
 }
 
 fn update_faction_relations(
-    // This system can be used to dynamically update faction relations based on player actions
-    // For now, it's a placeholder
+    mut change_reader: MessageReader<ReputationChange>,
+    mut relations: ResMut<FactionRelations>,
 ) {
-    // Placeholder for dynamic faction relation updates
+    for change in change_reader.read() {
+        let before = relations.standing(&change.faction);
+        relations.change_reputation(&change.faction, change.change);
+        let after = relations.standing(&change.faction);
+
+        println!(
+            "Reputation with {} changed by {:+.1} ({:.1}, {:?})",
+            change.faction.name(),
+            change.change,
+            relations.reputation(&change.faction),
+            after
+        );
+        if before != after {
+            println!("The {} now consider you {:?}.", change.faction.name(), after);
+        }
+    }
 }
 
 
-pub fn generate_random_encounter(_sector: u32) -> (Faction, ShipClass) {
-    let mut rng = rand::thread_rng();
-    
+pub fn generate_random_encounter(distance: u32, rng: &mut impl Rng) -> (Faction, ShipClass) {
+
     let faction = match rng.gen_range(0..100) {
         0..=20 => Faction::Cosmicons,
         21..=35 => Faction::Spirats,
@@ -133,14 +284,17 @@ pub fn generate_random_encounter(_sector: u32) -> (Faction, ShipClass) {
         76..=85 => Faction::Archs,
         _ => Faction::Neutral,
     };
-    
-    let ship_class = match rng.gen_range(0..100) {
+
+    (faction, roll_ship_class(distance, rng))
+}
+
+// Bigger ships turn up the further the drifter gets from where it started
+pub fn roll_ship_class(distance: u32, rng: &mut impl Rng) -> ShipClass {
+    match rng.gen_range(0..100) + distance {
         0..=40 => ShipClass::Scout,
         41..=65 => ShipClass::Fighter,
         66..=80 => ShipClass::Cruiser,
         81..=95 => ShipClass::Battleship,
         _ => ShipClass::Flagship,
-    };
-    
-    (faction, ship_class)
+    }
 }
@@ -1,73 +1,236 @@
+use bevy::ecs::schedule::ScheduleLabel;
 use bevy::prelude::*;
-use crate::{factions::FactionsPlugin, ship::ShipPlugin, sector::SectorPlugin, events::EventsPlugin, ui::UIPlugin};
+use serde::{Deserialize, Serialize};
+use crate::{actions::ActionsPlugin, factions::FactionsPlugin, ship::ShipPlugin, sector::SectorPlugin, events::EventsPlugin, event_library::EventLibraryPlugin, combat::CombatPlugin, enemy::EnemyPlugin, crew::CrewPlugin, store::StorePlugin, upgrades::UpgradesPlugin, life_support::LifeSupportPlugin, damage_control::DamageControlPlugin, environment::EnvironmentPlugin, sensors::SensorsPlugin, rng::RngPlugin};
+#[cfg(feature = "gui")]
+use crate::{map_view::MapViewPlugin, menu::MenuPlugin, save::SavePlugin, ui::UIPlugin};
+use crate::actions::{Action, Actions};
+use crate::combat::{ActiveCombat, CombatEnded, CombatResult, EnemyShip, WeaponControl};
+use crate::crew::CrewRoster;
+use crate::damage_control::CrewSelection;
+use crate::environment::Environment;
+use crate::events::ActiveEvent;
+use crate::life_support::LifeSupport;
+use crate::rng::RunRng;
+use crate::sensors::SensorScan;
+use crate::ship::{PlayerShip, PowerSelection, Ship, StartingShip};
+use crate::store::Store;
+use crate::upgrades::UpgradeScreen;
 
+// Jumps needed to reach the end of the run
+pub const VICTORY_DISTANCE: u32 = 30;
+
+// The full game: simulation plus everything the player sees and touches
+#[cfg(feature = "gui")]
 pub struct GamePlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins((GameLogicPlugin, MapViewPlugin, SavePlugin, UIPlugin, MenuPlugin))
+            .add_systems(Startup, spawn_camera);
+    }
+}
+
+// Gameplay rules only, with no window, rendering or save files, so a run can
+// also be driven headless
+pub struct GameLogicPlugin;
+
+// Sets up everything that belongs to one run; run once at startup and again
+// for every new run started from the menu
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RunStart;
+
+impl Plugin for GameLogicPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_state::<GameState>()
+            .insert_resource(RunStats::default())
             .add_plugins((
+                RngPlugin,
+                ActionsPlugin,
                 FactionsPlugin,
                 ShipPlugin,
                 SectorPlugin,
                 EventsPlugin,
-                UIPlugin,
+                EventLibraryPlugin,
+                CombatPlugin,
+                EnemyPlugin,
             ))
-            .add_systems(Startup, setup_game)
+            .add_plugins((
+                CrewPlugin,
+                StorePlugin,
+                UpgradesPlugin,
+                LifeSupportPlugin,
+                DamageControlPlugin,
+                EnvironmentPlugin,
+                SensorsPlugin,
+            ))
+            .add_systems(RunStart, setup_game)
+            .add_systems(PreStartup, start_first_run)
+            .add_systems(OnEnter(GameState::Paused), pause_time)
+            .add_systems(OnExit(GameState::Paused), resume_time)
+            .add_systems(Update, handle_input.run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))))
             .add_systems(Update, (
-                handle_input,
                 update_game_state,
-            ));
+                record_combat_results,
+                check_run_end,
+            ).run_if(in_state(GameState::Playing)));
+
+        // The first run uses the default setup on the session's seed
+        let seed = app.world().resource::<RunRng>().seed;
+        app.insert_resource(RunSetup { seed, ..default() });
     }
 }
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum GameState {
     #[default]
+    MainMenu,
+    // Picking the ship, difficulty and seed of a new run
+    NewRun,
+    Settings,
     Playing,
     Paused,
+    GameOver,
+    Victory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
 }
 
-#[derive(Resource)]
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    // Fuel and scrap the run starts with
+    fn starting_supplies(&self) -> (f32, u32) {
+        match self {
+            Difficulty::Easy => (60.0, 30),
+            Difficulty::Normal => (50.0, 15),
+            Difficulty::Hard => (40.0, 5),
+        }
+    }
+}
+
+// Choices made on the new run screen, read by the RunStart systems
+#[derive(Resource, Clone, Default)]
+pub struct RunSetup {
+    pub ship: StartingShip,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct GameData {
     pub current_sector: u32,
     pub fuel: f32,
     pub scrap: u32,
 }
 
+// Tallies shown on the run summary screen
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub enemies_destroyed: u32,
+    pub scrap_collected: u32,
+    pub cause_of_death: Option<String>,
+}
+
 
-fn setup_game(mut commands: Commands) {
+fn setup_game(mut commands: Commands, run_setup: Res<RunSetup>) {
     // Initialize game data
+    let (fuel, scrap) = run_setup.difficulty.starting_supplies();
     commands.insert_resource(GameData {
         current_sector: 0,
-        fuel: 50.0,
-        scrap: 15,
+        fuel,
+        scrap,
     });
+}
+
+fn start_first_run(world: &mut World) {
+    world.run_schedule(RunStart);
+}
+
+// Throws away the run in progress and starts a fresh one from the RunSetup
+pub fn start_new_run(world: &mut World) {
+    let seed = world.resource::<RunSetup>().seed;
+    println!("Run seed: {}", seed);
+
+    let ships: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<PlayerShip>, With<EnemyShip>)>>()
+        .iter(world)
+        .collect();
+    for ship in ships {
+        world.despawn(ship);
+    }
+    // The drawn map and the autosave only exist in the windowed game
+    #[cfg(feature = "gui")]
+    {
+        crate::map_view::clear_map_visual(world);
+        world.insert_resource(crate::save::Autosave::default());
+    }
 
-    // Spawn camera
+    // Per-run state that RunStart doesn't replace itself
+    world.insert_resource(RunRng::new(seed));
+    world.insert_resource(RunStats::default());
+    world.insert_resource(ActiveEvent::default());
+    world.insert_resource(ActiveCombat::default());
+    world.insert_resource(WeaponControl::default());
+    world.insert_resource(Store::default());
+    world.insert_resource(UpgradeScreen::default());
+    world.insert_resource(LifeSupport::default());
+    world.insert_resource(Environment::default());
+    world.insert_resource(SensorScan::default());
+    world.insert_resource(CrewSelection::default());
+    world.insert_resource(PowerSelection::default());
+
+    world.run_schedule(RunStart);
+    world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+}
+
+#[cfg(feature = "gui")]
+fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2d);
 }
 
 fn handle_input(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
     mut next_state: ResMut<NextState<GameState>>,
     current_state: Res<State<GameState>>,
 ) {
+    if !actions.just_performed(Action::Pause) {
+        return;
+    }
     match current_state.get() {
-        GameState::Playing => {
-            if keyboard.just_pressed(KeyCode::Escape) {
-                next_state.set(GameState::Paused);
-            }
-        }
-        GameState::Paused => {
-            if keyboard.just_pressed(KeyCode::Escape) {
-                next_state.set(GameState::Playing);
-            }
-        }
+        GameState::Playing => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::Playing),
+        // Menus and the run summary handle their own input
+        _ => {}
     }
 }
 
+// Timers and cooldowns hold still while the pause menu is up
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
 fn update_game_state(
     _game_data: ResMut<GameData>,
     _time: Res<Time>,
@@ -75,3 +238,55 @@ fn update_game_state(
     // Update game logic here
     // For now, just a placeholder
 }
+
+fn record_combat_results(
+    mut ended_reader: MessageReader<CombatEnded>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    for ended in ended_reader.read() {
+        match ended.result {
+            CombatResult::Victory => {
+                run_stats.enemies_destroyed += 1;
+                run_stats.scrap_collected += ended.salvage;
+            }
+            CombatResult::Defeat => {
+                run_stats.cause_of_death = Some(format!("Destroyed by the {}", ended.enemy_faction.name()));
+            }
+            CombatResult::Surrendered => {
+                run_stats.scrap_collected += ended.salvage;
+            }
+            CombatResult::Escaped => {}
+        }
+    }
+}
+
+fn check_run_end(
+    player_query: Query<&Ship, With<PlayerShip>>,
+    crew: Res<CrewRoster>,
+    sector_map: Res<crate::sector::SectorMap>,
+    mut run_stats: ResMut<RunStats>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Ok(ship) = player_query.single() {
+        if ship.is_destroyed() {
+            if run_stats.cause_of_death.is_none() {
+                run_stats.cause_of_death = Some("Hull integrity failure".to_string());
+            }
+            next_state.set(GameState::GameOver);
+            return;
+        }
+    }
+
+    // A ship with nobody left aboard is as lost as a destroyed one
+    if crew.members.is_empty() {
+        if run_stats.cause_of_death.is_none() {
+            run_stats.cause_of_death = Some("The last of the crew was lost".to_string());
+        }
+        next_state.set(GameState::GameOver);
+        return;
+    }
+
+    if sector_map.distance_traveled >= VICTORY_DISTANCE {
+        next_state.set(GameState::Victory);
+    }
+}
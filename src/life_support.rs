@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::crew::{CrewRoster, MAX_CREW_HEALTH};
+use crate::game::GameState;
+use crate::ship::{PlayerShip, Ship, SystemKind};
+
+pub struct LifeSupportPlugin;
+
+impl Plugin for LifeSupportPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(LifeSupport::default())
+            .add_systems(Update, (
+                update_oxygen,
+                suffocate_crew,
+                heal_crew,
+            ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+pub const MAX_OXYGEN: f32 = 100.0;
+// Oxygen percent restored per second for each effective oxygen level
+const OXYGEN_REFILL_PER_LEVEL: f32 = 2.0;
+// Oxygen percent lost per second while the oxygen system is down
+const OXYGEN_LEAK_RATE: f32 = 2.0;
+// Extra oxygen percent lost per second through each hull breach
+const BREACH_LEAK_RATE: f32 = 3.0;
+// Crew start suffocating below this much oxygen
+pub const SUFFOCATION_THRESHOLD: f32 = 20.0;
+// Damage per second to each crew member with no oxygen left at all
+const SUFFOCATION_DAMAGE: f32 = 5.0;
+// Health restored per second for each effective medbay level
+const MEDBAY_HEAL_PER_LEVEL: f32 = 1.0;
+
+// Breathable air aboard the player's ship, in percent
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct LifeSupport {
+    pub oxygen: f32,
+}
+
+impl Default for LifeSupport {
+    fn default() -> Self {
+        Self { oxygen: MAX_OXYGEN }
+    }
+}
+
+fn update_oxygen(
+    mut life_support: ResMut<LifeSupport>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    time: Res<Time>,
+) {
+    let Ok(ship) = player_query.single() else { return; };
+    let oxygen_system = &ship.systems.oxygen;
+
+    // A working system refills the air, a broken or unpowered one lets it leak away
+    let mut rate = if oxygen_system.is_functional() {
+        OXYGEN_REFILL_PER_LEVEL * oxygen_system.effective_level()
+    } else {
+        -OXYGEN_LEAK_RATE
+    };
+    let breaches = SystemKind::ALL.iter()
+        .filter(|system| ship.systems.module(**system).hazards.breach)
+        .count();
+    rate -= BREACH_LEAK_RATE * breaches as f32;
+
+    let oxygen = (life_support.oxygen + rate * time.delta_secs()).clamp(0.0, MAX_OXYGEN);
+    if oxygen != life_support.oxygen {
+        if oxygen < SUFFOCATION_THRESHOLD && life_support.oxygen >= SUFFOCATION_THRESHOLD {
+            println!("Warning: oxygen is running low!");
+        }
+        life_support.oxygen = oxygen;
+    }
+}
+
+fn suffocate_crew(
+    life_support: Res<LifeSupport>,
+    mut crew: ResMut<CrewRoster>,
+    time: Res<Time>,
+) {
+    if life_support.oxygen >= SUFFOCATION_THRESHOLD || crew.members.is_empty() {
+        return;
+    }
+
+    // Damage grows as the air thins out
+    let severity = 1.0 - life_support.oxygen / SUFFOCATION_THRESHOLD;
+    let damage = SUFFOCATION_DAMAGE * severity * time.delta_secs();
+    for member in &mut crew.members {
+        member.health = (member.health - damage).max(0.0);
+    }
+
+    crew.remove_dead("has suffocated");
+}
+
+fn heal_crew(
+    mut crew: ResMut<CrewRoster>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    time: Res<Time>,
+) {
+    let Ok(ship) = player_query.single() else { return; };
+    let heal_rate = MEDBAY_HEAL_PER_LEVEL * ship.systems.medbay.effective_level();
+
+    // Only touch the roster when someone is actually hurt
+    if heal_rate <= 0.0 || crew.members.iter().all(|member| member.health >= MAX_CREW_HEALTH) {
+        return;
+    }
+
+    for member in &mut crew.members {
+        member.health = (member.health + heal_rate * time.delta_secs()).min(MAX_CREW_HEALTH);
+    }
+}
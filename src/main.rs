@@ -1,15 +1,55 @@
+// Without the `gui` feature the helpers that only the HUD and menus call go unused
+#![cfg_attr(not(feature = "gui"), allow(dead_code))]
+
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 mod game;
+#[cfg(feature = "gui")]
+mod menu;
+mod actions;
 mod factions;
 mod ship;
 mod sector;
+#[cfg(feature = "gui")]
+mod map_view;
+mod map_generator;
 mod events;
+mod event_library;
+mod combat;
+mod enemy;
+mod crew;
+mod store;
+mod upgrades;
+mod life_support;
+mod damage_control;
+mod environment;
+mod sensors;
+#[cfg(feature = "gui")]
+mod save;
+mod autopilot;
+mod simulation;
+mod rng;
+#[cfg(feature = "gui")]
 mod ui;
 
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "gui")]
 use game::GamePlugin;
 
 fn main() {
+    // `--headless` plays runs on autopilot without a window and writes their statistics
+    if let Some(options) = simulation::SimulationOptions::from_args() {
+        simulation::run(options);
+        return;
+    }
+    run_game();
+}
+
+#[cfg(feature = "gui")]
+fn run_game() {
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -21,4 +61,11 @@ fn main() {
         }))
         .add_plugins(GamePlugin)
         .run();
-}
\ No newline at end of file
+}
+
+// Built with `--no-default-features` there is no window to open
+#[cfg(not(feature = "gui"))]
+fn run_game() {
+    eprintln!("This build only has the simulation; run it with --headless");
+    std::process::exit(2);
+}
@@ -0,0 +1,289 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use crate::sector::SectorType;
+
+// How likely a sector type is, once the map is far enough in for it to appear
+#[derive(Clone)]
+pub struct SectorWeight {
+    pub sector_type: SectorType,
+    pub weight: u32,
+    pub min_distance: u32,
+}
+
+// Shape of one layered sector map
+#[derive(Clone)]
+pub struct MapParams {
+    // Id of the entry node; the rest are numbered on from it
+    pub first_id: u32,
+    // Distance from the start of the run of the entry layer
+    pub start_distance: u32,
+    pub layers: RangeInclusive<usize>,
+    pub nodes_per_layer: RangeInclusive<usize>,
+    // Most links a node makes back to the previous layer
+    pub max_back_links: usize,
+    // Type of the entry node, rolled like any other if left empty
+    pub entry_type: Option<SectorType>,
+    pub sector_weights: Vec<SectorWeight>,
+    pub seed: u64,
+}
+
+impl Default for MapParams {
+    fn default() -> Self {
+        let weight = |sector_type, weight, min_distance| SectorWeight { sector_type, weight, min_distance };
+        Self {
+            first_id: 0,
+            start_distance: 0,
+            layers: 5..=7,
+            nodes_per_layer: 2..=4,
+            max_back_links: 2,
+            entry_type: None,
+            // Rarer, richer and more dangerous sectors only turn up deeper in
+            sector_weights: vec![
+                weight(SectorType::Empty, 26, 0),
+                weight(SectorType::Nebula, 15, 0),
+                weight(SectorType::AsteroidField, 16, 0),
+                weight(SectorType::Station, 11, 0),
+                weight(SectorType::Distress, 10, 0),
+                weight(SectorType::Combat, 10, 0),
+                weight(SectorType::Anomaly, 8, 0),
+                weight(SectorType::CelestialSite, 2, 11),
+                weight(SectorType::DarkRift, 2, 21),
+                weight(SectorType::AetheriumField, 1, 31),
+            ],
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GeneratedNode {
+    pub id: u32,
+    pub distance: u32,
+    pub sector_type: SectorType,
+}
+
+// Node graph of one sector map. Edges run between neighbouring layers, lower
+// layer first, and every node of the last layer leads on to `exit_id`.
+pub struct GeneratedMap {
+    pub nodes: Vec<GeneratedNode>,
+    pub edges: Vec<(u32, u32)>,
+    pub layers: Vec<Vec<u32>>,
+    // Id the next map's entry node will get
+    pub exit_id: u32,
+}
+
+pub fn generate_map(params: &MapParams) -> GeneratedMap {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let layer_count = rng.gen_range(params.layers.clone()).max(1);
+
+    let mut nodes = Vec::new();
+    let mut layers: Vec<Vec<u32>> = Vec::new();
+    let mut edges = Vec::new();
+    let mut next_id = params.first_id;
+
+    for layer in 0..layer_count {
+        // The map is entered through a single node
+        let node_count = if layer == 0 { 1 } else { rng.gen_range(params.nodes_per_layer.clone()).max(1) };
+        let distance = params.start_distance + layer as u32;
+
+        let mut current = Vec::new();
+        for _ in 0..node_count {
+            let sector_type = match (&params.entry_type, layer) {
+                (Some(entry_type), 0) => entry_type.clone(),
+                _ => roll_sector_type(&params.sector_weights, distance, &mut rng),
+            };
+            nodes.push(GeneratedNode { id: next_id, distance, sector_type });
+            current.push(next_id);
+            next_id += 1;
+        }
+
+        if let Some(previous) = layers.last() {
+            edges.extend(link_layers(previous, &current, params.max_back_links, &mut rng));
+        }
+        layers.push(current);
+    }
+
+    GeneratedMap { nodes, edges, layers, exit_id: next_id }
+}
+
+// Every node links back to one or more nodes of the previous layer, and every
+// node of the previous layer gets at least one way forward
+fn link_layers(previous: &[u32], current: &[u32], max_back_links: usize, rng: &mut impl Rng) -> Vec<(u32, u32)> {
+    let mut edges = Vec::new();
+    let mut linked = HashSet::new();
+
+    for &to in current {
+        let count = rng.gen_range(1..=max_back_links.clamp(1, previous.len()));
+        for &from in previous.choose_multiple(rng, count) {
+            edges.push((from, to));
+            linked.insert(from);
+        }
+    }
+
+    for &from in previous {
+        if !linked.contains(&from) {
+            let to = current[rng.gen_range(0..current.len())];
+            edges.push((from, to));
+        }
+    }
+
+    edges
+}
+
+fn roll_sector_type(weights: &[SectorWeight], distance: u32, rng: &mut impl Rng) -> SectorType {
+    let available: Vec<&SectorWeight> = weights.iter()
+        .filter(|entry| entry.min_distance <= distance && entry.weight > 0)
+        .collect();
+    let total: u32 = available.iter().map(|entry| entry.weight).sum();
+    if total == 0 {
+        return SectorType::Empty;
+    }
+
+    let mut roll = rng.gen_range(0..total);
+    for entry in available {
+        if roll < entry.weight {
+            return entry.sector_type.clone();
+        }
+        roll -= entry.weight;
+    }
+    SectorType::Empty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, VecDeque};
+
+    fn adjacency(map: &GeneratedMap) -> HashMap<u32, Vec<u32>> {
+        let mut adjacency: HashMap<u32, Vec<u32>> = HashMap::new();
+        for node in &map.nodes {
+            adjacency.entry(node.id).or_default();
+        }
+        for &(from, to) in &map.edges {
+            adjacency.entry(from).or_default().push(to);
+            adjacency.entry(to).or_default().push(from);
+        }
+        for &id in map.layers.last().unwrap() {
+            adjacency.entry(id).or_default().push(map.exit_id);
+        }
+        adjacency
+    }
+
+    fn reachable_from(map: &GeneratedMap, start: u32) -> HashSet<u32> {
+        let adjacency = adjacency(map);
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(id) = queue.pop_front() {
+            for &next in adjacency.get(&id).into_iter().flatten() {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    fn layer_of(map: &GeneratedMap) -> HashMap<u32, usize> {
+        map.layers.iter().enumerate()
+            .flat_map(|(layer, ids)| ids.iter().map(move |&id| (id, layer)))
+            .collect()
+    }
+
+    fn maps() -> impl Iterator<Item = GeneratedMap> {
+        (0..200).map(|seed| generate_map(&MapParams { seed, ..MapParams::default() }))
+    }
+
+    #[test]
+    fn every_node_is_reachable_from_the_entry() {
+        for map in maps() {
+            let reachable = reachable_from(&map, map.layers[0][0]);
+            for node in &map.nodes {
+                assert!(reachable.contains(&node.id), "node {} is cut off", node.id);
+            }
+        }
+    }
+
+    #[test]
+    fn exit_is_reachable_from_every_node_moving_forward() {
+        for map in maps() {
+            let layer_of = layer_of(&map);
+            let last_layer = map.layers.len() - 1;
+            for node in &map.nodes {
+                // Follow forward edges only, so no node is a dead end
+                let mut frontier = vec![node.id];
+                let mut layer = layer_of[&node.id];
+                while layer < last_layer {
+                    frontier = map.edges.iter()
+                        .filter(|(from, _)| frontier.contains(from))
+                        .map(|&(_, to)| to)
+                        .collect();
+                    assert!(!frontier.is_empty(), "node {} has no way forward", node.id);
+                    layer = layer_of[&frontier[0]];
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_duplicate_edges() {
+        for map in maps() {
+            let mut seen = HashSet::new();
+            for &(from, to) in &map.edges {
+                assert_ne!(from, to);
+                assert!(seen.insert((from.min(to), from.max(to))), "duplicate edge {}-{}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn respects_layer_and_node_counts() {
+        let params = MapParams { layers: 3..=4, nodes_per_layer: 1..=2, ..MapParams::default() };
+        for seed in 0..100 {
+            let map = generate_map(&MapParams { seed, ..params.clone() });
+            assert!(params.layers.contains(&map.layers.len()));
+            assert_eq!(map.layers[0].len(), 1);
+            for layer in &map.layers[1..] {
+                assert!(params.nodes_per_layer.contains(&layer.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn entry_type_and_distances_follow_params() {
+        let params = MapParams {
+            start_distance: 12,
+            entry_type: Some(SectorType::Station),
+            seed: 3,
+            ..MapParams::default()
+        };
+        let map = generate_map(&params);
+        let layer_of = layer_of(&map);
+        assert_eq!(map.nodes[0].sector_type, SectorType::Station);
+        for node in &map.nodes {
+            assert_eq!(node.distance, 12 + layer_of[&node.id] as u32);
+        }
+    }
+
+    #[test]
+    fn sector_types_wait_for_their_minimum_distance() {
+        let params = MapParams { start_distance: 0, layers: 5..=5, ..MapParams::default() };
+        for seed in 0..100 {
+            let map = generate_map(&MapParams { seed, ..params.clone() });
+            for node in &map.nodes {
+                assert_ne!(node.sector_type, SectorType::DarkRift);
+                assert_ne!(node.sector_type, SectorType::AetheriumField);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_map() {
+        let params = MapParams { seed: 99, ..MapParams::default() };
+        let (first, second) = (generate_map(&params), generate_map(&params));
+        assert_eq!(first.edges, second.edges);
+        assert_eq!(first.layers, second.layers);
+    }
+}
@@ -0,0 +1,411 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::events;
+use crate::rng::RunRng;
+use crate::sector::{NavigationSystemSet, Sector, SectorMap, try_travel_to_sector};
+use crate::sensors::{Detail, SensorScan};
+
+// Draws the sector map and lets the player click nodes to travel; needs a window
+pub struct MapViewPlugin;
+
+impl Plugin for MapViewPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_map_visual)
+            .add_systems(Update, (
+                update_map_visual,
+                update_armada_front,
+                handle_node_clicks,
+            ).in_set(NavigationSystemSet));
+    }
+}
+
+#[derive(Component)]
+pub struct MapNode {
+    pub _sector_id: u32,
+}
+
+#[derive(Component)]
+pub struct NodeLabel {
+    pub _sector_id: u32,
+}
+
+#[derive(Component)]
+pub struct ConnectionLine {
+}
+
+// Marks the edge of the space held by the Arch armada
+#[derive(Component)]
+pub struct ArmadaFront;
+
+#[derive(Resource)]
+pub struct MapVisual {
+    pub node_entities: HashMap<u32, Entity>,
+    pub connection_entities: Vec<Entity>,
+}
+
+// Drops the drawn map nodes so they are rebuilt for a new or loaded map
+pub fn clear_map_visual(world: &mut World) {
+    let nodes: Vec<Entity> = world
+        .query_filtered::<Entity, With<MapNode>>()
+        .iter(world)
+        .collect();
+    for node in nodes {
+        world.despawn(node);
+    }
+    if let Some(mut map_visual) = world.get_resource_mut::<MapVisual>() {
+        map_visual.node_entities.clear();
+    }
+}
+
+fn setup_map_visual(mut commands: Commands) {
+    commands.insert_resource(MapVisual {
+        node_entities: HashMap::new(),
+        connection_entities: Vec::new(),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_map_visual(
+    mut commands: Commands,
+    windows: Query<&Window>,
+    sector_map: Res<SectorMap>,
+    mut map_visual: ResMut<MapVisual>,
+    node_query: Query<(Entity, &MapNode)>,
+    connection_query: Query<Entity, (With<ConnectionLine>, Without<MapNode>)>,
+    label_query: Query<Entity, With<NodeLabel>>,
+    sensor_scan: Res<SensorScan>,
+) {
+    // Get window size to adapt the map
+    let Ok(window) = windows.single() else { return; };
+    let window_width = window.width();
+    let window_height = window.height();
+    
+    // Calculate positions for all sectors (procedural layout, adapted to window)
+    let mut positions = HashMap::new();
+    calculate_sector_positions(&sector_map, &mut positions, window_width, window_height);
+    
+    // Create/update nodes
+    for (sector_id, sector) in sector_map.sectors.iter() {
+        if !map_visual.node_entities.contains_key(sector_id) {
+            if let Some(&pos) = positions.get(sector_id) {
+                let is_current = *sector_id == sector_map.current_sector_id;
+                let color = node_color(sector, is_current, sensor_scan.detail(*sector_id));
+                
+                let size = if is_current { 15.0 } else { 10.0 };
+                
+                let node_entity = commands.spawn((
+                    MapNode {
+                        _sector_id: *sector_id,
+                    },
+                    Sprite {
+                        color,
+                        custom_size: Some(Vec2::new(size, size)),
+                        ..default()
+                    },
+                    Transform::from_translation(Vec3::new(pos.x, pos.y, 1.0)),
+                )).id();
+                
+                
+                map_visual.node_entities.insert(*sector_id, node_entity);
+            }
+        } else {
+            // Update existing node position and color
+            if let Some(&pos) = positions.get(sector_id) {
+                let is_current = *sector_id == sector_map.current_sector_id;
+                let color = node_color(sector, is_current, sensor_scan.detail(*sector_id));
+                
+                if let Ok((entity, _)) = node_query.get(*map_visual.node_entities.get(sector_id).unwrap()) {
+                    commands.entity(entity).insert((
+                        Sprite {
+                            color,
+                            custom_size: Some(Vec2::new(if is_current { 15.0 } else { 10.0 }, if is_current { 15.0 } else { 10.0 })),
+                            ..default()
+                        },
+                        Transform::from_translation(Vec3::new(pos.x, pos.y, 1.0)),
+                    ));
+                }
+            }
+        }
+    }
+    
+    // Update labels for connected nodes
+    for entity in label_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    
+    // Recreate labels for nodes connected to current sector (show numbers)
+    if let Some(current_sector) = sector_map.sectors.get(&sector_map.current_sector_id) {
+        let mut seen = std::collections::HashSet::new();
+        let mut label_index = 0;
+        
+        for &connected_id in &current_sector.connections {
+            // Skip if already seen or doesn't exist
+            if seen.contains(&connected_id) || !sector_map.sectors.contains_key(&connected_id) {
+                continue;
+            }
+            seen.insert(connected_id);
+            
+            if let Some(&pos) = positions.get(&connected_id) {
+                label_index += 1;
+                commands.spawn((
+                    NodeLabel { _sector_id: connected_id },
+                    Text2d::new(format!("{}", label_index)),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(1.0, 1.0, 0.0)),
+                    Transform::from_translation(Vec3::new(pos.x, pos.y - 25.0, 2.0)),
+                ));
+            }
+        }
+    }
+    
+    // Create connection lines
+    let mut existing_connections = std::collections::HashSet::new();
+    for entity in connection_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    map_visual.connection_entities.clear();
+    
+    for (sector_id, sector) in sector_map.sectors.iter() {
+        if let Some(&from_pos) = positions.get(sector_id) {
+            for &connected_id in &sector.connections {
+                if let Some(&to_pos) = positions.get(&connected_id) {
+                    // Avoid duplicate connections
+                    let connection_key = if sector_id < &connected_id {
+                        (*sector_id, connected_id)
+                    } else {
+                        (connected_id, *sector_id)
+                    };
+                    
+                    if !existing_connections.contains(&connection_key) {
+                        existing_connections.insert(connection_key);
+                        
+                        // Create line between nodes
+                        let mid_point = (from_pos + to_pos) / 2.0;
+                        let direction = to_pos - from_pos;
+                        let length = direction.length();
+                        let angle = direction.y.atan2(direction.x);
+                        
+                        let line_entity = commands.spawn((
+                            ConnectionLine {
+                            },
+                            Sprite {
+                                color: Color::srgb(0.3, 0.3, 0.3),
+                                custom_size: Some(Vec2::new(length, 2.0)),
+                                ..default()
+                            },
+                            Transform {
+                                translation: Vec3::new(mid_point.x, mid_point.y, 0.0),
+                                rotation: Quat::from_rotation_z(angle),
+                                ..default()
+                            },
+                        )).id();
+                        
+                        map_visual.connection_entities.push(line_entity);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Armada front - a red line just ahead of the last layer it holds. Only moves
+// when the map does or the window is resized, so it isn't rebuilt every frame
+fn update_armada_front(
+    mut commands: Commands,
+    windows: Query<&Window>,
+    sector_map: Res<SectorMap>,
+    front_query: Query<Entity, With<ArmadaFront>>,
+    mut drawn_for: Local<Vec2>,
+) {
+    let Ok(window) = windows.single() else { return; };
+    let window_size = Vec2::new(window.width(), window.height());
+    if !sector_map.is_changed() && *drawn_for == window_size {
+        return;
+    }
+    *drawn_for = window_size;
+
+    for entity in front_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut positions = HashMap::new();
+    calculate_sector_positions(&sector_map, &mut positions, window_size.x, window_size.y);
+    let depths = sector_map.sector_depths();
+    let layer_x = |depth: i32| {
+        depths.iter()
+            .find(|(_, sector_depth)| **sector_depth as i32 == depth)
+            .and_then(|(sector_id, _)| positions.get(sector_id))
+            .map(|pos| pos.x)
+    };
+    if let (Some(held_x), Some(next_x)) = (layer_x(sector_map.armada_depth), layer_x(sector_map.armada_depth + 1)) {
+        commands.spawn((
+            ArmadaFront,
+            Sprite {
+                color: Color::srgba(0.9, 0.1, 0.1, 0.6),
+                custom_size: Some(Vec2::new(4.0, window_size.y - 100.0)),
+                ..default()
+            },
+            Transform::from_translation(Vec3::new((held_x + next_x) / 2.0, 0.0, 0.5)),
+        ));
+    }
+}
+
+fn node_color(sector: &Sector, is_current: bool, detail: Detail) -> Color {
+    if is_current {
+        Color::srgb(0.0, 1.0, 0.0) // Green for current
+    } else if sector.overtaken {
+        Color::srgb(0.8, 0.2, 0.2) // Red for armada space
+    } else if sector.visited {
+        Color::srgb(0.5, 0.5, 0.5) // Gray for visited
+    } else if detail == Detail::Unknown {
+        Color::srgb(0.25, 0.25, 0.35) // Dim for beyond sensor range
+    } else {
+        Color::srgb(0.8, 0.8, 0.8) // White for unvisited
+    }
+}
+
+fn calculate_sector_positions(
+    sector_map: &SectorMap,
+    positions: &mut HashMap<u32, Vec2>,
+    window_width: f32,
+    window_height: f32,
+) {
+    // Simple layout: sectors arranged in layers based on distance
+    // Each layer is a row, sectors spread horizontally
+    let mut layer_map: HashMap<u32, Vec<u32>> = HashMap::new();
+    
+    // BFS to assign layers
+    let mut queue = std::collections::VecDeque::new();
+    let mut visited = std::collections::HashSet::new();
+    queue.push_back((0, 0)); // (sector_id, layer)
+    visited.insert(0);
+    
+    while let Some((sector_id, layer)) = queue.pop_front() {
+        layer_map.entry(layer).or_default().push(sector_id);
+        
+        if let Some(sector) = sector_map.sectors.get(&sector_id) {
+            for &connected_id in &sector.connections {
+                if !visited.contains(&connected_id) {
+                    visited.insert(connected_id);
+                    queue.push_back((connected_id, layer + 1));
+                }
+            }
+        }
+    }
+    
+    // Calculate spacing based on window size and number of layers
+    let num_layers = layer_map.len().max(1) as f32;
+    let max_nodes_per_layer = layer_map.values().map(|v| v.len()).max().unwrap_or(1) as f32;
+    
+    // Leave margins on all sides
+    let margin_x = 100.0;
+    let margin_y = 100.0;
+    let available_width = window_width - (2.0 * margin_x);
+    let available_height = window_height - (2.0 * margin_y);
+    
+    // Calculate spacing to fit everything within window
+    let layer_spacing = if num_layers > 1.0 {
+        available_width / (num_layers - 1.0)
+    } else {
+        0.0
+    };
+    let node_spacing = if max_nodes_per_layer > 1.0 {
+        available_height / (max_nodes_per_layer - 1.0)
+    } else {
+        0.0
+    };
+    
+    // Center the map
+    let start_x = -window_width / 2.0 + margin_x;
+    let center_y = 0.0; // Center vertically
+    
+    for (layer, sector_ids) in layer_map.iter() {
+        let layer_x = start_x + (*layer as f32 * layer_spacing);
+        let count = sector_ids.len() as f32;
+        let total_height = if count > 1.0 { (count - 1.0) * node_spacing } else { 0.0 };
+        let start_y_offset = center_y - (total_height / 2.0);
+        
+        for (i, &sector_id) in sector_ids.iter().enumerate() {
+            let y = start_y_offset + (i as f32 * node_spacing);
+            positions.insert(sector_id, Vec2::new(layer_x, y));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_node_clicks(
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    node_query: Query<(Entity, &MapNode, &Transform)>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut sector_map: ResMut<SectorMap>,
+    mut game_data: ResMut<crate::game::GameData>,
+    mut event_writer: MessageWriter<events::GameEvent>,
+    active_event: ResMut<events::ActiveEvent>,
+    active_combat: Res<crate::combat::ActiveCombat>,
+    store: Res<crate::store::Store>,
+    upgrade_screen: Res<crate::upgrades::UpgradeScreen>,
+    relations: Res<crate::factions::FactionRelations>,
+    library: Res<crate::event_library::EventLibrary>,
+    mut run_rng: ResMut<RunRng>,
+    ui_buttons: Query<&Interaction, With<Button>>,
+) {
+    // Don't allow clicking nodes if an event, fight or menu is currently active
+    if active_event.event.is_some() || active_combat.is_active() || store.open || upgrade_screen.open {
+        return;
+    }
+    // A click on a button (like an event choice that just closed) isn't meant for the map
+    if ui_buttons.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        return;
+    }
+    
+    if mouse_button.just_pressed(MouseButton::Left) {
+        if let Ok(window) = windows.single() {
+            if let Some(cursor_pos) = window.cursor_position() {
+                if let Ok((_camera, camera_transform)) = camera_query.single() {
+                    // Convert screen position to world position for 2D camera
+                    let window_size = Vec2::new(window.width(), window.height());
+                    
+                    // Get camera position
+                    let camera_pos = camera_transform.translation();
+                    
+                    // For 2D camera with default settings, convert cursor to world coordinates
+                    // Bevy 2D uses a coordinate system where (0,0) is at the center
+                    let cursor_world_x = (cursor_pos.x - window_size.x / 2.0) + camera_pos.x;
+                    let cursor_world_y = (window_size.y / 2.0 - cursor_pos.y) + camera_pos.y;
+                    let cursor_world = Vec2::new(cursor_world_x, cursor_world_y);
+                    
+                    // Check if click is on a node
+                    for (_entity, map_node, node_transform) in node_query.iter() {
+                        let node_pos = Vec2::new(node_transform.translation.x, node_transform.translation.y);
+                        let distance = (cursor_world - node_pos).length();
+                        
+                        // Click radius (node size + some padding)
+                        if distance < 30.0 {
+                            // Check if this node is connected to current sector
+                            if let Some(current_sector) = sector_map.sectors.get(&sector_map.current_sector_id) {
+                                if current_sector.connections.contains(&map_node._sector_id) {
+                                    // Travel to this sector
+                                    try_travel_to_sector(
+                                        &mut sector_map,
+                                        &mut game_data,
+                                        map_node._sector_id,
+                                        &mut event_writer,
+                                        active_event,
+                                        &relations,
+                                        &library,
+                                        &mut run_rng,
+                                    );
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
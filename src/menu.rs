@@ -0,0 +1,315 @@
+use bevy::prelude::*;
+use crate::actions::{Action, Actions, Bindings, BINDINGS_FILE};
+use crate::game::{Difficulty, GameState, RunSetup, start_new_run};
+use crate::rng::seed_from_args;
+use crate::save::{latest_save_slot, load_slot, save_to_quicksave};
+use crate::ship::StartingShip;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(GameState::NewRun), (roll_seed, spawn_new_run_menu).chain())
+            .add_systems(OnEnter(GameState::Settings), spawn_settings_menu)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(Update, (
+                handle_menu_input,
+                highlight_menu_buttons,
+                update_menu_labels,
+            ).run_if(in_menu));
+    }
+}
+
+fn in_menu(state: Res<State<GameState>>) -> bool {
+    matches!(state.get(), GameState::MainMenu | GameState::NewRun | GameState::Settings | GameState::Paused)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuItem {
+    NewRun,
+    Continue,
+    Settings,
+    Quit,
+    CycleShip,
+    CycleDifficulty,
+    RerollSeed,
+    Launch,
+    Back,
+    Resume,
+    Save,
+    QuitToMenu,
+}
+
+impl MenuItem {
+    fn label(&self, run_setup: &RunSetup) -> String {
+        match self {
+            MenuItem::NewRun => "New Run".to_string(),
+            MenuItem::Continue => "Continue".to_string(),
+            MenuItem::Settings => "Settings".to_string(),
+            MenuItem::Quit => "Quit".to_string(),
+            MenuItem::CycleShip => format!("Ship: {} - {}", run_setup.ship.name(), run_setup.ship.description()),
+            MenuItem::CycleDifficulty => format!("Difficulty: {}", run_setup.difficulty.name()),
+            MenuItem::RerollSeed => format!("Seed: {} (roll another)", run_setup.seed),
+            MenuItem::Launch => "Launch".to_string(),
+            MenuItem::Back => "Back".to_string(),
+            MenuItem::Resume => "Resume".to_string(),
+            MenuItem::Save => "Save".to_string(),
+            MenuItem::QuitToMenu => "Quit to main menu".to_string(),
+        }
+    }
+}
+
+// Buttons are numbered in the order they are listed, so number keys and
+// gamepad buttons can pick them too
+#[derive(Component)]
+struct MenuButton {
+    index: usize,
+    item: MenuItem,
+    enabled: bool,
+}
+
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.17, 0.25);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.25, 0.28, 0.4);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.35, 0.4, 0.55);
+const BUTTON_DISABLED_COLOR: Color = Color::srgb(0.1, 0.1, 0.12);
+
+fn spawn_main_menu(mut commands: Commands, run_setup: Res<RunSetup>) {
+    let items = [
+        (MenuItem::NewRun, true),
+        // Continue picks up the last save, so it needs one to exist
+        (MenuItem::Continue, latest_save_slot().is_some()),
+        (MenuItem::Settings, true),
+        (MenuItem::Quit, true),
+    ];
+    spawn_menu(&mut commands, GameState::MainMenu, "STAR DRIFTER", None, &items, &run_setup);
+}
+
+// A fresh seed every time the screen opens, unless one was given on the command line
+fn roll_seed(mut run_setup: ResMut<RunSetup>) {
+    run_setup.seed = seed_from_args().unwrap_or_else(rand::random);
+}
+
+fn spawn_new_run_menu(mut commands: Commands, run_setup: Res<RunSetup>) {
+    let items = [
+        (MenuItem::CycleShip, true),
+        (MenuItem::CycleDifficulty, true),
+        (MenuItem::RerollSeed, true),
+        (MenuItem::Launch, true),
+        (MenuItem::Back, true),
+    ];
+    spawn_menu(&mut commands, GameState::NewRun, "NEW RUN", None, &items, &run_setup);
+}
+
+fn spawn_settings_menu(mut commands: Commands, run_setup: Res<RunSetup>, bindings: Res<Bindings>) {
+    let controls = [
+        ("Pause", Action::Pause),
+        ("Confirm", Action::Confirm),
+        ("Cancel", Action::Cancel),
+        ("Store", Action::ToggleStore),
+        ("Upgrades", Action::ToggleUpgrades),
+        ("Target", Action::CycleTarget),
+        ("Autofire", Action::ToggleAutofire),
+        ("Fire", Action::Fire),
+        ("Save", Action::Quicksave),
+        ("Load", Action::Quickload),
+    ];
+    let mut text = format!("Choices, jumps and purchases: {}\n", bindings.number_label());
+    for (name, action) in controls {
+        text.push_str(&format!("{}: {}\n", name, bindings.label(action)));
+    }
+    text.push_str(&format!("\nKeys and gamepad buttons are rebound in {} and read at startup.", BINDINGS_FILE));
+
+    let items = [(MenuItem::Back, true)];
+    spawn_menu(&mut commands, GameState::Settings, "SETTINGS", Some(text), &items, &run_setup);
+}
+
+fn spawn_pause_menu(mut commands: Commands, run_setup: Res<RunSetup>) {
+    let items = [
+        (MenuItem::Resume, true),
+        (MenuItem::Save, true),
+        (MenuItem::QuitToMenu, true),
+    ];
+    spawn_menu(&mut commands, GameState::Paused, "PAUSED", None, &items, &run_setup);
+}
+
+// A full-screen panel that goes away when its state is left
+fn spawn_menu(
+    commands: &mut Commands,
+    state: GameState,
+    title: &str,
+    body: Option<String>,
+    items: &[(MenuItem, bool)],
+    run_setup: &RunSetup,
+) {
+    // The pause menu lets the frozen run show through
+    let background = if state == GameState::Paused {
+        Color::srgba(0.0, 0.0, 0.0, 0.7)
+    } else {
+        Color::srgb(0.03, 0.03, 0.06)
+    };
+
+    commands
+        .spawn((
+            DespawnOnExit(state),
+            Node {
+                position_type: PositionType::Absolute,
+                width: percent(100.0),
+                height: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: px(8.0),
+                ..default()
+            },
+            BackgroundColor(background),
+            // Above the HUD and event panels
+            GlobalZIndex(10),
+        ))
+        .with_children(|menu| {
+            menu.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 1.0)),
+                Node {
+                    margin: UiRect::bottom(px(16.0)),
+                    ..default()
+                },
+            ));
+
+            if let Some(body) = body {
+                menu.spawn((
+                    Text::new(body),
+                    TextFont {
+                        font_size: 18.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.7, 0.7, 0.8)),
+                    Node {
+                        margin: UiRect::bottom(px(16.0)),
+                        ..default()
+                    },
+                ));
+            }
+
+            for (index, (item, enabled)) in items.iter().copied().enumerate() {
+                let (background, text_color) = if enabled {
+                    (BUTTON_COLOR, Color::WHITE)
+                } else {
+                    (BUTTON_DISABLED_COLOR, Color::srgb(0.45, 0.45, 0.45))
+                };
+                menu.spawn((
+                    Button,
+                    MenuButton { index, item, enabled },
+                    Node {
+                        min_width: px(320.0),
+                        padding: UiRect::axes(px(16.0), px(8.0)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    BackgroundColor(background),
+                    children![(
+                        Text::new(format!("{}. {}", index + 1, item.label(run_setup))),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(text_color),
+                    )],
+                ));
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_menu_input(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    clicked_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    button_query: Query<&MenuButton>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run_setup: ResMut<RunSetup>,
+    mut exit_writer: MessageWriter<AppExit>,
+) {
+    let clicked = clicked_query.iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button);
+    let numbered = actions.index_of(Action::MenuOption)
+        .and_then(|index| button_query.iter().find(|button| button.index == index));
+
+    let item = match clicked.or(numbered) {
+        Some(button) if button.enabled => button.item,
+        Some(_) => return,
+        // Escape and Cancel back out of the sub-screens; the pause key itself resumes a paused run
+        None => match state.get() {
+            GameState::NewRun if actions.just_performed(Action::Confirm) => MenuItem::Launch,
+            GameState::NewRun | GameState::Settings
+                if actions.just_performed(Action::Pause) || actions.just_performed(Action::Cancel) => MenuItem::Back,
+            _ => return,
+        },
+    };
+
+    match item {
+        MenuItem::NewRun => next_state.set(GameState::NewRun),
+        MenuItem::Continue => {
+            commands.queue(|world: &mut World| {
+                if let Some(slot) = latest_save_slot() {
+                    load_slot(world, slot);
+                }
+            });
+        }
+        MenuItem::Settings => next_state.set(GameState::Settings),
+        MenuItem::Quit => {
+            exit_writer.write(AppExit::Success);
+        }
+        MenuItem::CycleShip => run_setup.ship = next_in(&StartingShip::ALL, run_setup.ship),
+        MenuItem::CycleDifficulty => run_setup.difficulty = next_in(&Difficulty::ALL, run_setup.difficulty),
+        MenuItem::RerollSeed => run_setup.seed = rand::random(),
+        MenuItem::Launch => commands.queue(start_new_run),
+        MenuItem::Back | MenuItem::QuitToMenu => next_state.set(GameState::MainMenu),
+        MenuItem::Resume => next_state.set(GameState::Playing),
+        MenuItem::Save => commands.queue(save_to_quicksave),
+    }
+}
+
+fn next_in<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let index = options.iter().position(|option| *option == current).unwrap_or(0);
+    options[(index + 1) % options.len()]
+}
+
+// Disabled buttons stay dark whatever the mouse does
+fn highlight_menu_buttons(
+    mut button_query: Query<(&Interaction, &MenuButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        background.0 = match (button.enabled, interaction) {
+            (false, _) => BUTTON_DISABLED_COLOR,
+            (true, Interaction::Pressed) => BUTTON_PRESSED_COLOR,
+            (true, Interaction::Hovered) => BUTTON_HOVER_COLOR,
+            (true, Interaction::None) => BUTTON_COLOR,
+        };
+    }
+}
+
+// The new run screen shows the current pick on each button
+fn update_menu_labels(
+    run_setup: Res<RunSetup>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !run_setup.is_changed() {
+        return;
+    }
+    for (button, children) in button_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                **text = format!("{}. {}", button.index + 1, button.item.label(&run_setup));
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        // An app built around a fixed seed (e.g. a headless simulation) keeps it
+        if app.world().contains_resource::<RunRng>() {
+            return;
+        }
+        let seed = seed_from_args().unwrap_or_else(|| rand::thread_rng().gen());
+        println!("Run seed: {}", seed);
+        app.insert_resource(RunRng::new(seed));
+    }
+}
+
+// Every random roll in a run comes from one of these streams, all derived
+// from the run seed, so the same seed always replays the same galaxy
+#[derive(Resource)]
+pub struct RunRng {
+    pub seed: u64,
+    pub map: StdRng,
+    pub events: StdRng,
+    pub names: StdRng,
+    pub combat: StdRng,
+    pub store: StdRng,
+    pub environment: StdRng,
+}
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        Self::resume(seed, 0)
+    }
+
+    // Restarts the streams for a run resumed partway through, keyed on how far it got
+    pub fn resume(seed: u64, distance: u32) -> Self {
+        Self {
+            seed,
+            map: derive_stream(seed, "map", distance),
+            events: derive_stream(seed, "events", distance),
+            names: derive_stream(seed, "names", distance),
+            combat: derive_stream(seed, "combat", distance),
+            store: derive_stream(seed, "store", distance),
+            environment: derive_stream(seed, "environment", distance),
+        }
+    }
+}
+
+fn derive_stream(seed: u64, subsystem: &str, distance: u32) -> StdRng {
+    // FNV-1a keeps the derivation stable across Rust versions, unlike DefaultHasher
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in subsystem.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash ^= (distance as u64).wrapping_mul(0x9e3779b97f4a7c15);
+    StdRng::seed_from_u64(seed ^ hash)
+}
+
+// Accepts `--seed 1234` or `--seed=1234` on the command line
+pub fn seed_from_args() -> Option<u64> {
+    let args: Vec<String> = std::env::args().collect();
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix("--seed=") {
+            return value.parse().ok();
+        }
+        if arg == "--seed" {
+            return args.get(i + 1).and_then(|value| value.parse().ok());
+        }
+    }
+    None
+}
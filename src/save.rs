@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use crate::actions::{Action, Actions};
+use crate::combat::{ActiveCombat, EnemyShip};
+use crate::crew::CrewRoster;
+use crate::events::ActiveEvent;
+use crate::factions::{Faction, FactionRelations};
+use crate::game::{GameData, GameState, RunStats};
+use crate::life_support::LifeSupport;
+use crate::rng::RunRng;
+use crate::map_view::clear_map_visual;
+use crate::sector::{SectorMap, armada_start};
+use crate::ship::{PlayerShip, PowerDistribution, Ship, Shields};
+use crate::store::Store;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Autosave>()
+            .add_systems(Update, (
+                handle_save_input,
+                autosave_on_arrival,
+            ).run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Bump whenever the layout of SaveFile changes, new defaulted fields included,
+// and add a migration below
+pub const SAVE_VERSION: u32 = 2;
+pub const SAVE_DIRECTORY: &str = "saves";
+const QUICKSAVE_SLOT: &str = "slot_1";
+const AUTOSAVE_SLOT: &str = "autosave";
+
+// Distance of the last autosave, so each arrival is saved once; reset with the run
+#[derive(Resource, Default)]
+pub struct Autosave {
+    pub last_distance: u32,
+    // Arrival whose autosave already failed, so the retries don't repeat the message
+    pub failed_distance: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub game_data: GameData,
+    pub sector_map: SectorMap,
+    pub ship: Ship,
+    pub shields: Shields,
+    pub power: PowerDistribution,
+    pub active_event: ActiveEvent,
+    pub crew: CrewRoster,
+    pub reputation: HashMap<Faction, f32>,
+    pub run_stats: RunStats,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub life_support: LifeSupport,
+}
+
+// Only the version is read first so older layouts can be routed to a migration
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+// The parts of a version 1 save whose defaults don't fit a run already under way
+#[derive(Deserialize)]
+struct SaveV1Header {
+    sector_map: SectorMapV1Header,
+}
+
+#[derive(Deserialize)]
+struct SectorMapV1Header {
+    #[serde(default)]
+    armada_depth: Option<i32>,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion(u32),
+    InCombat,
+    MissingPlayerShip,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "file error: {}", err),
+            SaveError::Parse(err) => write!(f, "invalid save data: {}", err),
+            SaveError::Serialize(err) => write!(f, "could not encode save: {}", err),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "save version {} is not supported (current is {})", version, SAVE_VERSION)
+            }
+            SaveError::InCombat => write!(f, "cannot save during combat"),
+            SaveError::MissingPlayerShip => write!(f, "no player ship to save"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(err: std::io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        SaveError::Parse(err)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(err: ron::Error) -> Self {
+        SaveError::Serialize(err)
+    }
+}
+
+pub fn slot_path(slot: &str) -> PathBuf {
+    PathBuf::from(SAVE_DIRECTORY).join(format!("{}.ron", slot))
+}
+
+pub fn write_save(slot: &str, save: &SaveFile) -> Result<(), SaveError> {
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+    let contents = ron::ser::to_string_pretty(save, ron::ser::PrettyConfig::default())?;
+    fs::write(slot_path(slot), contents)?;
+    Ok(())
+}
+
+pub fn read_save(slot: &str) -> Result<SaveFile, SaveError> {
+    let contents = fs::read_to_string(slot_path(slot))?;
+    let header: SaveHeader = ron::from_str(&contents)?;
+    migrate(header.version, &contents)
+}
+
+fn migrate(version: u32, contents: &str) -> Result<SaveFile, SaveError> {
+    match version {
+        SAVE_VERSION => Ok(ron::from_str(contents)?),
+        1 => migrate_v1(contents),
+        _ => Err(SaveError::UnsupportedVersion(version)),
+    }
+}
+
+// Version 1 predates the run seed, life support, station owners, the armada and
+// charted sectors. Those all start from their defaults, except the armada, which
+// is put its usual head start behind the player instead of back at the first sector
+fn migrate_v1(contents: &str) -> Result<SaveFile, SaveError> {
+    let mut save: SaveFile = ron::from_str(contents)?;
+    let header: SaveV1Header = ron::from_str(contents)?;
+    if header.sector_map.armada_depth.is_none() {
+        save.sector_map.armada_depth = armada_start() + save.sector_map.distance_traveled as i32;
+    }
+    save.version = SAVE_VERSION;
+    Ok(save)
+}
+
+pub fn capture_save(world: &mut World) -> Result<SaveFile, SaveError> {
+    if world.resource::<ActiveCombat>().is_active() {
+        return Err(SaveError::InCombat);
+    }
+
+    let (ship, shields) = world
+        .query_filtered::<(&Ship, &Shields), With<PlayerShip>>()
+        .single(world)
+        .map(|(ship, shields)| (ship.clone(), shields.clone()))
+        .map_err(|_| SaveError::MissingPlayerShip)?;
+
+    Ok(SaveFile {
+        version: SAVE_VERSION,
+        game_data: world.resource::<GameData>().clone(),
+        sector_map: world.resource::<SectorMap>().clone(),
+        ship,
+        shields,
+        power: world.resource::<PowerDistribution>().clone(),
+        active_event: world.resource::<ActiveEvent>().clone(),
+        crew: world.resource::<CrewRoster>().clone(),
+        reputation: world.resource::<FactionRelations>().reputation_table().clone(),
+        run_stats: world.resource::<RunStats>().clone(),
+        seed: world.resource::<RunRng>().seed,
+        life_support: world.resource::<LifeSupport>().clone(),
+    })
+}
+
+pub fn restore_save(world: &mut World, mut save: SaveFile) {
+    // Drop any fight in progress along with its enemy
+    let enemies: Vec<Entity> = world
+        .query_filtered::<Entity, With<EnemyShip>>()
+        .iter(world)
+        .collect();
+    for enemy in enemies {
+        world.despawn(enemy);
+    }
+    world.insert_resource(ActiveCombat::default());
+    // The store restocks once the loaded sector is re-entered
+    world.insert_resource(Store::default());
+
+    // Shield timers are relative to the current session clock
+    save.shields.last_hit_time = 0.0;
+    if let Ok(mut player) = world
+        .query_filtered::<(&mut Ship, &mut Shields), With<PlayerShip>>()
+        .single_mut(world)
+    {
+        *player.0 = save.ship;
+        *player.1 = save.shields;
+    }
+
+    // Rolls continue from the saved seed rather than the session's own
+    world.insert_resource(RunRng::resume(save.seed, save.sector_map.distance_traveled));
+    // The loaded sector is already in the save, the next arrival isn't
+    world.insert_resource(Autosave { last_distance: save.sector_map.distance_traveled, failed_distance: None });
+    world.insert_resource(save.game_data);
+    world.insert_resource(save.sector_map);
+    world.insert_resource(save.power);
+    world.insert_resource(save.active_event);
+    world.insert_resource(save.crew);
+    world.insert_resource(save.run_stats);
+    world.insert_resource(save.life_support);
+    world.resource_mut::<FactionRelations>().restore_reputation(save.reputation);
+
+    // The map visuals are rebuilt from scratch for the loaded sectors
+    clear_map_visual(world);
+
+    world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+}
+
+fn handle_save_input(world: &mut World) {
+    let actions = world.resource::<Actions>();
+    let quicksave = actions.just_performed(Action::Quicksave);
+    let quickload = actions.just_performed(Action::Quickload);
+    let load_autosave = actions.just_performed(Action::LoadAutosave);
+
+    if quicksave {
+        save_to_quicksave(world);
+    }
+
+    if quickload {
+        load_slot(world, QUICKSAVE_SLOT);
+    } else if load_autosave {
+        load_slot(world, AUTOSAVE_SLOT);
+    }
+}
+
+pub fn save_to_quicksave(world: &mut World) {
+    match capture_save(world).and_then(|save| write_save(QUICKSAVE_SLOT, &save)) {
+        Ok(()) => println!("Game saved to {}", slot_path(QUICKSAVE_SLOT).display()),
+        Err(err) => println!("Save failed: {}", err),
+    }
+}
+
+pub fn load_slot(world: &mut World, slot: &str) {
+    match read_save(slot) {
+        Ok(save) => {
+            restore_save(world, save);
+            println!("Loaded {}", slot_path(slot).display());
+        }
+        Err(err) => println!("Load failed: {}", err),
+    }
+}
+
+// The save written last, quick or auto, which is what Continue picks up
+pub fn latest_save_slot() -> Option<&'static str> {
+    [QUICKSAVE_SLOT, AUTOSAVE_SLOT].into_iter()
+        .filter_map(|slot| {
+            let modified = fs::metadata(slot_path(slot)).and_then(|metadata| metadata.modified()).ok()?;
+            Some((modified, slot))
+        })
+        .max()
+        .map(|(_, slot)| slot)
+}
+
+fn autosave_on_arrival(world: &mut World) {
+    let distance = world.resource::<SectorMap>().distance_traveled;
+    // An arrival that opens with a fight is saved once the fight is over
+    if distance == world.resource::<Autosave>().last_distance || world.resource::<ActiveCombat>().is_active() {
+        return;
+    }
+
+    // Only a save that made it to disk counts; a failed one is tried again next frame
+    let result = capture_save(world).and_then(|save| write_save(AUTOSAVE_SLOT, &save));
+    let mut autosave = world.resource_mut::<Autosave>();
+    match result {
+        Ok(()) => {
+            autosave.last_distance = distance;
+            autosave.failed_distance = None;
+            println!("Autosaved to {}", slot_path(AUTOSAVE_SLOT).display());
+        }
+        Err(err) => {
+            if autosave.failed_distance != Some(distance) {
+                autosave.failed_distance = Some(distance);
+                println!("Autosave failed: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestGame;
+
+    #[test]
+    fn version_1_saves_are_migrated() {
+        // A save from before the armada was recorded, taken six jumps into the run
+        let mut game = TestGame::new();
+        game.resource_mut::<SectorMap>().distance_traveled = 6;
+        let mut save = capture_save(game.world_mut()).expect("save");
+        save.version = 1;
+        let contents = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()).expect("encode");
+        let contents: Vec<&str> = contents.lines()
+            .filter(|line| !line.trim_start().starts_with("armada_depth:"))
+            .collect();
+
+        let save = migrate(1, &contents.join("\n")).expect("migrated");
+
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(save.sector_map.armada_depth, armada_start() + 6);
+    }
+}
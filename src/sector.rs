@@ -1,8 +1,12 @@
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::factions::{Faction, generate_random_encounter};
+use crate::factions::{Faction, ShipClass, generate_random_encounter, roll_ship_class};
+use crate::actions::{Action, Actions};
 use crate::events;
+use crate::map_generator::{MapParams, generate_map};
+use crate::rng::RunRng;
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NavigationSystemSet;
@@ -12,24 +16,64 @@ pub struct SectorPlugin;
 impl Plugin for SectorPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, (setup_sector_map, setup_map_visual))
-            .configure_sets(Update, NavigationSystemSet.after(crate::events::EventSystemSet))
-            .add_systems(Update, (
-                handle_sector_navigation,
-                update_map_visual,
-                handle_node_clicks,
-            ).in_set(NavigationSystemSet));
+            .add_systems(crate::game::RunStart, setup_sector_map)
+            .configure_sets(Update, NavigationSystemSet
+                .after(crate::events::EventSystemSet)
+                .run_if(in_state(crate::game::GameState::Playing)))
+            .add_systems(Update, handle_sector_navigation.in_set(NavigationSystemSet));
     }
 }
 
-#[derive(Resource)]
+// Jumps of head start the player has on the Arch armada
+const ARMADA_HEAD_START: i32 = 3;
+// Danger added to every sector the armada overtakes
+const ARMADA_DANGER: u32 = 4;
+// Armada warships are rolled as if this much further into the run
+const ARMADA_CLASS_BONUS: u32 = 40;
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct SectorMap {
     pub current_sector_id: u32,
     pub sectors: HashMap<u32, Sector>,
     pub distance_traveled: u32, // For scaling difficulty
+    // Map depth the pursuing Arch armada has reached
+    #[serde(default = "armada_start")]
+    pub armada_depth: i32,
+}
+
+pub fn armada_start() -> i32 {
+    -ARMADA_HEAD_START
 }
 
-#[derive(Clone)]
+impl SectorMap {
+    // Jumps from the start of the run to every known sector
+    pub fn sector_depths(&self) -> HashMap<u32, u32> {
+        let mut depths = HashMap::new();
+        let mut queue = std::collections::VecDeque::new();
+        depths.insert(0, 0);
+        queue.push_back(0);
+
+        while let Some(sector_id) = queue.pop_front() {
+            let depth = depths[&sector_id];
+            let Some(sector) = self.sectors.get(&sector_id) else { continue; };
+            for &connected_id in &sector.connections {
+                if self.sectors.contains_key(&connected_id) && !depths.contains_key(&connected_id) {
+                    depths.insert(connected_id, depth + 1);
+                    queue.push_back(connected_id);
+                }
+            }
+        }
+        depths
+    }
+
+    // Jumps between the player and the armada front; zero or less means it has caught up
+    pub fn armada_gap(&self) -> i32 {
+        let depth = self.sector_depths().get(&self.current_sector_id).copied().unwrap_or(0);
+        depth as i32 - self.armada_depth
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Sector {
     pub _id: u32,
     pub sector_type: SectorType,
@@ -39,9 +83,18 @@ pub struct Sector {
     pub visited: bool,
     pub events: Vec<SectorEvent>,
     pub danger_level: u32,
+    // Faction running the sector's station, if it has one
+    #[serde(default)]
+    pub owner: Option<Faction>,
+    // Held by the Arch armada
+    #[serde(default)]
+    pub overtaken: bool,
+    // Revealed by map data bought at a station
+    #[serde(default)]
+    pub charted: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SectorType {
     Empty,          // Nothing of interest
     Nebula,         // Reduced sensors, possible hiding spots
@@ -87,194 +140,118 @@ impl SectorType {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SectorEvent {
     pub event_type: EventType,
     pub description: String,
     pub faction: Option<Faction>,
+    // Class of the ship behind an encounter
+    #[serde(default)]
+    pub ship_class: Option<ShipClass>,
     pub _triggered: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventType {
     Encounter,
     Discovery,
     Hazard,
     Opportunity,
     Story,
+    // The pursuing armada blocks the way
+    Pursuit,
 }
 
-#[derive(Component)]
-pub struct MapNode {
-    pub _sector_id: u32,
-}
+fn setup_sector_map(mut commands: Commands, mut run_rng: ResMut<RunRng>) {
+    let mut sectors = HashMap::new();
+    // The run always starts docked at a station
+    generate_map_chunk(&mut sectors, 0, 0, Some(SectorType::Station), &mut run_rng);
 
-#[derive(Component)]
-pub struct NodeLabel {
-    pub _sector_id: u32,
+    commands.insert_resource(SectorMap {
+        current_sector_id: 0,
+        sectors,
+        distance_traveled: 0,
+        armada_depth: armada_start(),
+    });
 }
 
-#[derive(Component)]
-pub struct ConnectionLine {
-}
+// Generates one layered map (like FTL) starting at `first_id` and adds its sectors.
+// Every sector of the last layer gets the next map's entry id as its final connection.
+fn generate_map_chunk(
+    sectors: &mut HashMap<u32, Sector>,
+    first_id: u32,
+    start_distance: u32,
+    entry_type: Option<SectorType>,
+    rng: &mut RunRng,
+) {
+    let map = generate_map(&MapParams {
+        first_id,
+        start_distance,
+        entry_type,
+        seed: rng.map.gen(),
+        ..MapParams::default()
+    });
 
-#[derive(Resource)]
-pub struct MapVisual {
-    pub node_entities: HashMap<u32, Entity>,
-    pub connection_entities: Vec<Entity>,
-}
+    for node in map.nodes {
+        let sector = generate_sector(node.id, node.sector_type, rng, node.distance);
+        sectors.insert(node.id, sector);
+    }
 
-fn setup_sector_map(mut commands: Commands) {
-    let mut sectors = HashMap::new();
-    let mut rng = rand::thread_rng();
-    
-    // Generate a complete procedural map (like FTL)
-    // Create 5-7 layers with 2-4 nodes per layer
-    let num_layers = rng.gen_range(5..=7);
-    let mut next_id = 0u32;
-    let mut layer_nodes: Vec<Vec<u32>> = Vec::new();
-    
-    // Generate first layer (starting sector)
-    let starting_sector = generate_sector(
-        next_id,
-        SectorType::Station,
-        &mut rng,
-        0,
-    );
-    sectors.insert(next_id, starting_sector);
-    layer_nodes.push(vec![next_id]);
-    next_id += 1;
-    
-    // Generate remaining layers
-    for layer in 1..num_layers {
-        let nodes_in_layer = rng.gen_range(2..=4);
-        let mut current_layer = Vec::new();
-        
-        for _ in 0..nodes_in_layer {
-            let distance = layer as u32;
-            let sector_type = generate_random_sector_type(&mut rng, distance);
-            let sector = generate_sector(next_id, sector_type, &mut rng, distance);
-            sectors.insert(next_id, sector);
-            current_layer.push(next_id);
-            next_id += 1;
+    for (from, to) in map.edges {
+        if let Some(sector) = sectors.get_mut(&to) {
+            sector.connections.push(from);
         }
-        
-        // Connect to previous layer
-        let prev_layer = &layer_nodes[layer - 1];
-        for &current_id in &current_layer {
-            // Each node connects to 1-2 nodes from previous layer
-            let num_connections = rng.gen_range(1..=2.min(prev_layer.len()));
-            let mut connected = std::collections::HashSet::new();
-            let mut connections_to_add = Vec::new();
-            
-            for _ in 0..num_connections {
-                let target_id = prev_layer[rng.gen_range(0..prev_layer.len())];
-                if !connected.contains(&target_id) {
-                    connected.insert(target_id);
-                    connections_to_add.push(target_id);
-                }
-            }
-            
-            // Add forward connections
-            if let Some(sector) = sectors.get_mut(&current_id) {
-                sector.connections.extend(connections_to_add.iter().copied());
-            }
-            
-            // Add reverse connections
-            for &target_id in &connections_to_add {
-                if let Some(target_sector) = sectors.get_mut(&target_id) {
-                    target_sector.connections.push(current_id);
-                }
-            }
+        if let Some(sector) = sectors.get_mut(&from) {
+            sector.connections.push(to);
         }
-        
-        layer_nodes.push(current_layer);
     }
-    
-    // Add exit node to the last layer - connects to next sector (auto-generated)
-    if let Some(last_layer) = layer_nodes.last() {
-        let next_sector_id = next_id;
-        for &sector_id in last_layer {
-            if let Some(sector) = sectors.get_mut(&sector_id) {
-                sector.connections.push(next_sector_id);
-            }
+
+    for sector_id in map.layers.last().into_iter().flatten() {
+        if let Some(sector) = sectors.get_mut(sector_id) {
+            sector.connections.push(map.exit_id);
         }
     }
-    
-    commands.insert_resource(SectorMap {
-        current_sector_id: 0,
-        sectors,
-        distance_traveled: 0,
-    });
 }
 
 fn generate_sector(
     id: u32,
     sector_type: SectorType,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut RunRng,
     distance: u32,
 ) -> Sector {
-    let name = generate_sector_name(&sector_type, id);
+    let name = generate_sector_name(&sector_type, id, &mut rng.names);
     let description = sector_type.description().to_string();
-    let events = generate_sector_events(&sector_type, rng);
+    let events = generate_sector_events(&sector_type, distance, &mut rng.map);
     let danger_level = calculate_danger_level(distance, &sector_type);
-    
-    // Each sector has only ONE connection: the next sector (id + 1)
-    let connections = vec![id + 1];
-    
+    let owner = generate_station_owner(&sector_type, &mut rng.map);
+
     Sector {
         _id: id,
         sector_type,
         name,
         description,
-        connections,
+        connections: Vec::new(),
         visited: false,
         events,
         danger_level,
+        owner,
+        overtaken: false,
+        charted: false,
     }
 }
 
-
-fn generate_random_sector_type(rng: &mut rand::rngs::ThreadRng, distance: u32) -> SectorType {
-    // Scale rarity with distance traveled
-    let distance_factor = (distance as f32 / 10.0).min(5.0); // Cap at 5x
-    
-    match rng.gen_range(0..100) {
-        0..=25 => SectorType::Empty,
-        26..=40 => SectorType::Nebula,
-        41..=55 => SectorType::AsteroidField,
-        56..=65 => SectorType::Station,
-        66..=75 => SectorType::Distress,
-        76..=85 => SectorType::Combat,
-        86..=90 => SectorType::Anomaly,
-        91..=95 => {
-            // Dark Rift becomes more common as distance increases
-            if distance_factor > 2.0 && rng.gen_bool(0.3) {
-                SectorType::DarkRift
-            } else {
-                SectorType::Anomaly
-            }
-        }
-        96..=98 => {
-            // Celestial Sites appear more often at higher distances
-            if distance_factor > 1.0 && rng.gen_bool(0.4) {
-                SectorType::CelestialSite
-            } else {
-                SectorType::Station
-            }
-        }
-        _ => {
-            // Aetherium Fields are rare but scale with distance
-            if distance_factor > 3.0 && rng.gen_bool(0.2) {
-                SectorType::AetheriumField
-            } else {
-                SectorType::AsteroidField
-            }
-        }
+fn generate_station_owner(sector_type: &SectorType, rng: &mut impl Rng) -> Option<Faction> {
+    if *sector_type != SectorType::Station {
+        return None;
     }
+
+    // Independent stations are as common as any single major faction's
+    let roll = rng.gen_range(0..=Faction::MAJOR.len());
+    Some(Faction::MAJOR.get(roll).cloned().unwrap_or(Faction::Neutral))
 }
 
-fn generate_sector_name(sector_type: &SectorType, _id: u32) -> String {
+
+fn generate_sector_name(sector_type: &SectorType, _id: u32, rng: &mut impl Rng) -> String {
     let prefixes = match sector_type {
         SectorType::Empty => vec!["Void", "Silent", "Barren", "Hollow"],
         SectorType::Nebula => vec!["Crimson", "Azure", "Stellar", "Mystic"],
@@ -301,23 +278,23 @@ fn generate_sector_name(sector_type: &SectorType, _id: u32) -> String {
         SectorType::AetheriumField => vec!["Mines", "Crystals", "Deposits", "Veins"],
     };
 
-    let mut rng = rand::thread_rng();
     let prefix = prefixes[rng.gen_range(0..prefixes.len())];
     let suffix = suffixes[rng.gen_range(0..suffixes.len())];
     
     format!("{} {}", prefix, suffix)
 }
 
-fn generate_sector_events(sector_type: &SectorType, rng: &mut rand::rngs::ThreadRng) -> Vec<SectorEvent> {
+fn generate_sector_events(sector_type: &SectorType, distance: u32, rng: &mut impl Rng) -> Vec<SectorEvent> {
     let mut events = Vec::new();
     
     match sector_type {
         SectorType::Combat => {
-            let (faction, ship_class) = generate_random_encounter(0);
+            let (faction, ship_class) = generate_random_encounter(distance, rng);
             events.push(SectorEvent {
                 event_type: EventType::Encounter,
-                description: format!("A {} {} ship blocks your path!", faction.name(), format!("{:?}", ship_class)),
+                description: format!("A {} {} blocks your path!", faction.name(), ship_class.name()),
                 faction: Some(faction),
+                ship_class: Some(ship_class),
                 _triggered: false,
             });
         }
@@ -327,6 +304,7 @@ fn generate_sector_events(sector_type: &SectorType, rng: &mut rand::rngs::Thread
                     event_type: EventType::Opportunity,
                     description: "A damaged ship requests assistance.".to_string(),
                     faction: None,
+                    ship_class: None,
                     _triggered: false,
                 });
             } else {
@@ -334,6 +312,7 @@ fn generate_sector_events(sector_type: &SectorType, rng: &mut rand::rngs::Thread
                     event_type: EventType::Hazard,
                     description: "The distress signal is a trap!".to_string(),
                     faction: Some(Faction::Spirats),
+                    ship_class: None,
                     _triggered: false,
                 });
             }
@@ -343,6 +322,7 @@ fn generate_sector_events(sector_type: &SectorType, rng: &mut rand::rngs::Thread
                 event_type: EventType::Discovery,
                 description: "Rare Aetherium crystals detected! Mining could be profitable but dangerous.".to_string(),
                 faction: None,
+                ship_class: None,
                 _triggered: false,
             });
         }
@@ -351,17 +331,19 @@ fn generate_sector_events(sector_type: &SectorType, rng: &mut rand::rngs::Thread
                 event_type: EventType::Story,
                 description: "Ancient Celestial ruins pulse with mysterious energy.".to_string(),
                 faction: Some(Faction::Celestials),
+                ship_class: None,
                 _triggered: false,
             });
         }
         _ => {
             // Random chance for events in other sectors
             if rng.gen_bool(0.3) {
-                let (faction, _) = generate_random_encounter(0);
+                let (faction, ship_class) = generate_random_encounter(distance, rng);
                 events.push(SectorEvent {
                     event_type: EventType::Encounter,
                     description: format!("You encounter a {} patrol.", faction.name()),
                     faction: Some(faction),
+                    ship_class: Some(ship_class),
                     _triggered: false,
                 });
             }
@@ -377,19 +359,34 @@ fn calculate_danger_level(distance: u32, sector_type: &SectorType) -> u32 {
     base + distance_bonus
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_sector_navigation(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
     mut sector_map: ResMut<SectorMap>,
     mut game_data: ResMut<crate::game::GameData>,
     mut event_writer: MessageWriter<crate::events::GameEvent>,
     active_event: ResMut<crate::events::ActiveEvent>,
-    input_consumed: Res<crate::events::InputConsumed>,
+    active_combat: Res<crate::combat::ActiveCombat>,
+    store: Res<crate::store::Store>,
+    upgrade_screen: Res<crate::upgrades::UpgradeScreen>,
+    relations: Res<crate::factions::FactionRelations>,
+    library: Res<crate::event_library::EventLibrary>,
+    mut run_rng: ResMut<RunRng>,
 ) {
     // Don't allow navigation if an event is currently active
-    // Numbers should only be used for event choices when an event is active
     if active_event.event.is_some() {
         return;
     }
+
+    // No jumping away in the middle of a fight
+    if active_combat.is_active() {
+        return;
+    }
+
+    // No jumping away from an open store or upgrade screen
+    if store.open || upgrade_screen.open {
+        return;
+    }
     
     if let Some(current_sector) = sector_map.sectors.get(&sector_map.current_sector_id) {
         let connections = current_sector.connections.clone();
@@ -407,56 +404,45 @@ fn handle_sector_navigation(
                     exit_sector_id,
                     &mut event_writer,
                     active_event,
+                    &relations,
+                    &library,
+                    &mut run_rng,
                 );
                 return;
             }
         }
         
         // Handle navigation to other connected sectors using number keys 1-9
-        for (i, &target_id) in connections.iter().enumerate() {
-            // Skip the exit node (last one) - it's automatic
-            if i == connections.len() - 1 && !sector_map.sectors.contains_key(&target_id) {
-                continue;
-            }
-            
-            let key = match i {
-                0 => KeyCode::Digit1,
-                1 => KeyCode::Digit2,
-                2 => KeyCode::Digit3,
-                3 => KeyCode::Digit4,
-                4 => KeyCode::Digit5,
-                5 => KeyCode::Digit6,
-                6 => KeyCode::Digit7,
-                7 => KeyCode::Digit8,
-                8 => KeyCode::Digit9,
-                _ => continue,
-            };
-            
-            // Skip if this key was already consumed by event system
-            if input_consumed.keys.contains(&key) {
-                continue;
-            }
-            
-            if keyboard.just_pressed(key) {
-                try_travel_to_sector(
-                    &mut sector_map,
-                    &mut game_data,
-                    target_id,
-                    &mut event_writer,
-                    active_event,
-                );
-                break;
-            }
+        let Some(index) = actions.index_of(Action::TravelTo) else { return; };
+        let Some(&target_id) = connections.get(index) else { return; };
+        // The exit node is never picked by hand - it's automatic
+        if !sector_map.sectors.contains_key(&target_id) {
+            return;
         }
+
+        try_travel_to_sector(
+            &mut sector_map,
+            &mut game_data,
+            target_id,
+            &mut event_writer,
+            active_event,
+            &relations,
+            &library,
+            &mut run_rng,
+        );
     }
 }
 
-fn try_travel_to_sector(
+#[allow(clippy::too_many_arguments)]
+pub fn try_travel_to_sector(
     sector_map: &mut SectorMap,
     game_data: &mut crate::game::GameData,
     target_sector_id: u32,
     event_writer: &mut MessageWriter<events::GameEvent>,
     mut active_event: ResMut<events::ActiveEvent>,
+    relations: &crate::factions::FactionRelations,
+    library: &crate::event_library::EventLibrary,
+    run_rng: &mut RunRng,
 ) {
     // Check fuel
     if game_data.fuel < 1.0 {
@@ -472,70 +458,8 @@ fn try_travel_to_sector(
     
     // Generate new sector map if it doesn't exist (this is the exit node to next sector)
     if !sector_map.sectors.contains_key(&target_sector_id) {
-        let mut rng = rand::thread_rng();
         let distance = sector_map.distance_traveled + 1;
-        let num_layers = rng.gen_range(5..=7);
-        let mut next_id = target_sector_id;
-        let mut layer_nodes: Vec<Vec<u32>> = Vec::new();
-        
-        // Generate first layer of new sector
-        let sector_type = generate_random_sector_type(&mut rng, distance);
-        let starting_sector = generate_sector(next_id, sector_type, &mut rng, distance);
-        sector_map.sectors.insert(next_id, starting_sector);
-        layer_nodes.push(vec![next_id]);
-        next_id += 1;
-        
-        // Generate remaining layers
-        for layer in 1..num_layers {
-            let nodes_in_layer = rng.gen_range(2..=4);
-            let mut current_layer = Vec::new();
-            
-            for _ in 0..nodes_in_layer {
-                let sector_type = generate_random_sector_type(&mut rng, distance + layer as u32);
-                let sector = generate_sector(next_id, sector_type, &mut rng, distance + layer as u32);
-                sector_map.sectors.insert(next_id, sector);
-                current_layer.push(next_id);
-                next_id += 1;
-            }
-            
-            // Connect to previous layer
-            let prev_layer = &layer_nodes[layer - 1];
-            for &current_id in &current_layer {
-                let num_connections = rng.gen_range(1..=2.min(prev_layer.len()));
-                let mut connected = std::collections::HashSet::new();
-                let mut connections_to_add = Vec::new();
-                
-                for _ in 0..num_connections {
-                    let target_id = prev_layer[rng.gen_range(0..prev_layer.len())];
-                    if !connected.contains(&target_id) {
-                        connected.insert(target_id);
-                        connections_to_add.push(target_id);
-                    }
-                }
-                
-                if let Some(sector) = sector_map.sectors.get_mut(&current_id) {
-                    sector.connections.extend(connections_to_add.iter().copied());
-                }
-                
-                for &target_id in &connections_to_add {
-                    if let Some(target_sector) = sector_map.sectors.get_mut(&target_id) {
-                        target_sector.connections.push(current_id);
-                    }
-                }
-            }
-            
-            layer_nodes.push(current_layer);
-        }
-        
-        // Add exit node to the last layer
-        if let Some(last_layer) = layer_nodes.last() {
-            let next_sector_id = next_id;
-            for &sector_id in last_layer {
-                if let Some(sector) = sector_map.sectors.get_mut(&sector_id) {
-                    sector.connections.push(next_sector_id);
-                }
-            }
-        }
+        generate_map_chunk(&mut sector_map.sectors, target_sector_id, distance, None, run_rng);
     }
     
     // Travel to sector
@@ -548,303 +472,42 @@ fn try_travel_to_sector(
     if let Some(sector) = sector_map.sectors.get_mut(&target_sector_id) {
         sector.visited = true;
     }
+
+    advance_armada(sector_map, &mut run_rng.map);
     
     // Automatically trigger event for the new sector
-    events::trigger_event_for_sector(sector_map, target_sector_id, event_writer, &mut *active_event);
+    events::trigger_event_for_sector(sector_map, target_sector_id, event_writer, &mut active_event, relations, library, &mut run_rng.events);
 }
 
+// The armada moves one jump closer every time the player jumps, taking over every sector it reaches
+fn advance_armada(sector_map: &mut SectorMap, rng: &mut impl Rng) {
+    sector_map.armada_depth += 1;
+    let front = sector_map.armada_depth;
 
-// Helper function to get current sector (for UI)
-
-// Visual map system
-fn setup_map_visual(mut commands: Commands) {
-    commands.insert_resource(MapVisual {
-        node_entities: HashMap::new(),
-        connection_entities: Vec::new(),
-    });
-}
-
-fn update_map_visual(
-    mut commands: Commands,
-    windows: Query<&Window>,
-    sector_map: Res<SectorMap>,
-    mut map_visual: ResMut<MapVisual>,
-    node_query: Query<(Entity, &MapNode)>,
-    connection_query: Query<Entity, (With<ConnectionLine>, Without<MapNode>)>,
-    label_query: Query<Entity, With<NodeLabel>>,
-) {
-    // Get window size to adapt the map
-    let Ok(window) = windows.single() else { return; };
-    let window_width = window.width();
-    let window_height = window.height();
-    
-    // Calculate positions for all sectors (procedural layout, adapted to window)
-    let mut positions = HashMap::new();
-    calculate_sector_positions(&sector_map, &mut positions, window_width, window_height);
-    
-    // Create/update nodes
-    for (sector_id, sector) in sector_map.sectors.iter() {
-        if !map_visual.node_entities.contains_key(sector_id) {
-            if let Some(&pos) = positions.get(sector_id) {
-                let is_current = *sector_id == sector_map.current_sector_id;
-                let color = if is_current {
-                    Color::srgb(0.0, 1.0, 0.0) // Green for current
-                } else if sector.visited {
-                    Color::srgb(0.5, 0.5, 0.5) // Gray for visited
-                } else {
-                    Color::srgb(0.8, 0.8, 0.8) // White for unvisited
-                };
-                
-                let size = if is_current { 15.0 } else { 10.0 };
-                
-                let node_entity = commands.spawn((
-                    MapNode {
-                        _sector_id: *sector_id,
-                    },
-                    Sprite {
-                        color,
-                        custom_size: Some(Vec2::new(size, size)),
-                        ..default()
-                    },
-                    Transform::from_translation(Vec3::new(pos.x, pos.y, 1.0)),
-                )).id();
-                
-                
-                map_visual.node_entities.insert(*sector_id, node_entity);
-            }
-        } else {
-            // Update existing node position and color
-            if let Some(&pos) = positions.get(sector_id) {
-                let is_current = *sector_id == sector_map.current_sector_id;
-                let color = if is_current {
-                    Color::srgb(0.0, 1.0, 0.0)
-                } else if sector.visited {
-                    Color::srgb(0.5, 0.5, 0.5)
-                } else {
-                    Color::srgb(0.8, 0.8, 0.8)
-                };
-                
-                if let Ok((entity, _)) = node_query.get(*map_visual.node_entities.get(sector_id).unwrap()) {
-                    commands.entity(entity).insert((
-                        Sprite {
-                            color,
-                            custom_size: Some(Vec2::new(if is_current { 15.0 } else { 10.0 }, if is_current { 15.0 } else { 10.0 })),
-                            ..default()
-                        },
-                        Transform::from_translation(Vec3::new(pos.x, pos.y, 1.0)),
-                    ));
-                }
-            }
+    let mut overtaken: Vec<(u32, u32)> = sector_map.sector_depths().into_iter()
+        .filter(|(_, depth)| *depth as i32 <= front)
+        .collect();
+    // Sorted so the class rolls don't depend on hash map order
+    overtaken.sort();
+    for (sector_id, depth) in overtaken {
+        let Some(sector) = sector_map.sectors.get_mut(&sector_id) else { continue; };
+        if sector.overtaken {
+            continue;
         }
+        sector.overtaken = true;
+        sector.danger_level += ARMADA_DANGER;
+        sector.events = vec![SectorEvent {
+            event_type: EventType::Pursuit,
+            description: "Arch warships have taken this sector and are hunting for you.".to_string(),
+            faction: Some(Faction::Archs),
+            ship_class: Some(roll_ship_class(depth + ARMADA_CLASS_BONUS, rng)),
+            _triggered: false,
+        }];
     }
-    
-    // Update labels for connected nodes
-    for entity in label_query.iter() {
-        commands.entity(entity).despawn();
-    }
-    
-    // Recreate labels for nodes connected to current sector (show numbers)
-    if let Some(current_sector) = sector_map.sectors.get(&sector_map.current_sector_id) {
-        let mut seen = std::collections::HashSet::new();
-        let mut label_index = 0;
-        
-        for &connected_id in &current_sector.connections {
-            // Skip if already seen or doesn't exist
-            if seen.contains(&connected_id) || !sector_map.sectors.contains_key(&connected_id) {
-                continue;
-            }
-            seen.insert(connected_id);
-            
-            if let Some(&pos) = positions.get(&connected_id) {
-                label_index += 1;
-                commands.spawn((
-                    NodeLabel { _sector_id: connected_id },
-                    Text2d::new(format!("{}", label_index)),
-                    TextFont {
-                        font_size: 20.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(1.0, 1.0, 0.0)),
-                    Transform::from_translation(Vec3::new(pos.x, pos.y - 25.0, 2.0)),
-                ));
-            }
-        }
-    }
-    
-    // Create connection lines
-    let mut existing_connections = std::collections::HashSet::new();
-    for entity in connection_query.iter() {
-        commands.entity(entity).despawn();
-    }
-    map_visual.connection_entities.clear();
-    
-    for (sector_id, sector) in sector_map.sectors.iter() {
-        if let Some(&from_pos) = positions.get(sector_id) {
-            for &connected_id in &sector.connections {
-                if let Some(&to_pos) = positions.get(&connected_id) {
-                    // Avoid duplicate connections
-                    let connection_key = if sector_id < &connected_id {
-                        (*sector_id, connected_id)
-                    } else {
-                        (connected_id, *sector_id)
-                    };
-                    
-                    if !existing_connections.contains(&connection_key) {
-                        existing_connections.insert(connection_key);
-                        
-                        // Create line between nodes
-                        let mid_point = (from_pos + to_pos) / 2.0;
-                        let direction = to_pos - from_pos;
-                        let length = direction.length();
-                        let angle = direction.y.atan2(direction.x);
-                        
-                        let line_entity = commands.spawn((
-                            ConnectionLine {
-                            },
-                            Sprite {
-                                color: Color::srgb(0.3, 0.3, 0.3),
-                                custom_size: Some(Vec2::new(length, 2.0)),
-                                ..default()
-                            },
-                            Transform {
-                                translation: Vec3::new(mid_point.x, mid_point.y, 0.0),
-                                rotation: Quat::from_rotation_z(angle),
-                                ..default()
-                            },
-                        )).id();
-                        
-                        map_visual.connection_entities.push(line_entity);
-                    }
-                }
-            }
-        }
-    }
-}
 
-fn calculate_sector_positions(
-    sector_map: &SectorMap,
-    positions: &mut HashMap<u32, Vec2>,
-    window_width: f32,
-    window_height: f32,
-) {
-    // Simple layout: sectors arranged in layers based on distance
-    // Each layer is a row, sectors spread horizontally
-    let mut layer_map: HashMap<u32, Vec<u32>> = HashMap::new();
-    
-    // BFS to assign layers
-    let mut queue = std::collections::VecDeque::new();
-    let mut visited = std::collections::HashSet::new();
-    queue.push_back((0, 0)); // (sector_id, layer)
-    visited.insert(0);
-    
-    while let Some((sector_id, layer)) = queue.pop_front() {
-        layer_map.entry(layer).or_insert_with(Vec::new).push(sector_id);
-        
-        if let Some(sector) = sector_map.sectors.get(&sector_id) {
-            for &connected_id in &sector.connections {
-                if !visited.contains(&connected_id) {
-                    visited.insert(connected_id);
-                    queue.push_back((connected_id, layer + 1));
-                }
-            }
-        }
-    }
-    
-    // Calculate spacing based on window size and number of layers
-    let num_layers = layer_map.len().max(1) as f32;
-    let max_nodes_per_layer = layer_map.values().map(|v| v.len()).max().unwrap_or(1) as f32;
-    
-    // Leave margins on all sides
-    let margin_x = 100.0;
-    let margin_y = 100.0;
-    let available_width = window_width - (2.0 * margin_x);
-    let available_height = window_height - (2.0 * margin_y);
-    
-    // Calculate spacing to fit everything within window
-    let layer_spacing = if num_layers > 1.0 {
-        available_width / (num_layers - 1.0)
-    } else {
-        0.0
-    };
-    let node_spacing = if max_nodes_per_layer > 1.0 {
-        available_height / (max_nodes_per_layer - 1.0)
-    } else {
-        0.0
-    };
-    
-    // Center the map
-    let start_x = -window_width / 2.0 + margin_x;
-    let center_y = 0.0; // Center vertically
-    
-    for (layer, sector_ids) in layer_map.iter() {
-        let layer_x = start_x + (*layer as f32 * layer_spacing);
-        let count = sector_ids.len() as f32;
-        let total_height = if count > 1.0 { (count - 1.0) * node_spacing } else { 0.0 };
-        let start_y_offset = center_y - (total_height / 2.0);
-        
-        for (i, &sector_id) in sector_ids.iter().enumerate() {
-            let y = start_y_offset + (i as f32 * node_spacing);
-            positions.insert(sector_id, Vec2::new(layer_x, y));
-        }
+    match sector_map.armada_gap() {
+        gap if gap <= 0 => println!("The Arch armada has caught up with you!"),
+        gap if gap <= 2 => println!("The Arch armada is {} jump(s) behind you.", gap),
+        _ => {}
     }
 }
-
-fn handle_node_clicks(
-    windows: Query<&Window>,
-    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
-    node_query: Query<(Entity, &MapNode, &Transform)>,
-    mouse_button: Res<ButtonInput<MouseButton>>,
-    mut sector_map: ResMut<SectorMap>,
-    mut game_data: ResMut<crate::game::GameData>,
-    mut event_writer: MessageWriter<events::GameEvent>,
-    active_event: ResMut<events::ActiveEvent>,
-) {
-    // Don't allow clicking nodes if an event is currently active
-    if active_event.event.is_some() {
-        return;
-    }
-    
-    if mouse_button.just_pressed(MouseButton::Left) {
-        if let Ok(window) = windows.single() {
-            if let Some(cursor_pos) = window.cursor_position() {
-                if let Ok((_camera, camera_transform)) = camera_query.single() {
-                    // Convert screen position to world position for 2D camera
-                    let window_size = Vec2::new(window.width(), window.height());
-                    
-                    // Get camera position
-                    let camera_pos = camera_transform.translation();
-                    
-                    // For 2D camera with default settings, convert cursor to world coordinates
-                    // Bevy 2D uses a coordinate system where (0,0) is at the center
-                    let cursor_world_x = (cursor_pos.x - window_size.x / 2.0) + camera_pos.x;
-                    let cursor_world_y = (window_size.y / 2.0 - cursor_pos.y) + camera_pos.y;
-                    let cursor_world = Vec2::new(cursor_world_x, cursor_world_y);
-                    
-                    // Check if click is on a node
-                    for (_entity, map_node, node_transform) in node_query.iter() {
-                        let node_pos = Vec2::new(node_transform.translation.x, node_transform.translation.y);
-                        let distance = (cursor_world - node_pos).length();
-                        
-                        // Click radius (node size + some padding)
-                        if distance < 30.0 {
-                            // Check if this node is connected to current sector
-                            if let Some(current_sector) = sector_map.sectors.get(&sector_map.current_sector_id) {
-                                if current_sector.connections.contains(&map_node._sector_id) {
-                                    // Travel to this sector
-                                    try_travel_to_sector(
-                                        &mut sector_map,
-                                        &mut game_data,
-                                        map_node._sector_id,
-                                        &mut event_writer,
-                                        active_event,
-                                    );
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
\ No newline at end of file
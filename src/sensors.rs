@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use crate::environment::Environment;
+use crate::game::GameState;
+use crate::sector::SectorMap;
+use crate::ship::{PlayerShip, Ship};
+
+pub struct SensorsPlugin;
+
+impl Plugin for SensorsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(SensorScan::default())
+            .add_systems(Update, update_sensor_scan.run_if(in_state(GameState::Playing)));
+    }
+}
+
+// How much the player knows about a sector, from nothing up to its pending events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Detail {
+    #[default]
+    Unknown,
+    SectorType,
+    Danger,
+    Events,
+}
+
+impl Detail {
+    // Sensors see one jump further per level and pick up more detail as they go
+    fn for_level(level: u32) -> Self {
+        match level {
+            0 => Detail::Unknown,
+            1 => Detail::SectorType,
+            2 => Detail::Danger,
+            _ => Detail::Events,
+        }
+    }
+}
+
+// What the sensors currently know about each sector on the map
+#[derive(Resource, Default, PartialEq)]
+pub struct SensorScan {
+    pub level: u32,
+    details: HashMap<u32, Detail>,
+}
+
+impl SensorScan {
+    pub fn detail(&self, sector_id: u32) -> Detail {
+        self.details.get(&sector_id).copied().unwrap_or_default()
+    }
+}
+
+fn update_sensor_scan(
+    mut scan: ResMut<SensorScan>,
+    sector_map: Res<SectorMap>,
+    environment: Res<Environment>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+) {
+    let Ok(ship) = player_query.single() else { return; };
+    // A nebula blinds the sensors completely
+    let level = if environment.sensors_jammed() {
+        0
+    } else {
+        ship.systems.sensors.effective_level().round() as u32
+    };
+
+    let mut details = HashMap::new();
+    // Places the crew has been to or bought charts for are always known
+    for (sector_id, sector) in &sector_map.sectors {
+        if sector.visited || sector.charted || *sector_id == sector_map.current_sector_id {
+            details.insert(*sector_id, Detail::Events);
+        }
+    }
+
+    // Everything within `level` jumps shows up on the scan
+    let detail = Detail::for_level(level);
+    let mut queue = VecDeque::from([(sector_map.current_sector_id, 0)]);
+    let mut seen = vec![sector_map.current_sector_id];
+    while let Some((sector_id, jumps)) = queue.pop_front() {
+        if jumps >= level {
+            continue;
+        }
+        let Some(sector) = sector_map.sectors.get(&sector_id) else { continue; };
+        for &connected_id in &sector.connections {
+            if seen.contains(&connected_id) || !sector_map.sectors.contains_key(&connected_id) {
+                continue;
+            }
+            seen.push(connected_id);
+            let known = details.entry(connected_id).or_default();
+            *known = (*known).max(detail);
+            queue.push_back((connected_id, jumps + 1));
+        }
+    }
+
+    // Only write on change so the map and sector panel aren't redrawn every frame
+    let new_scan = SensorScan { level, details };
+    if *scan != new_scan {
+        *scan = new_scan;
+    }
+}
@@ -1,32 +1,79 @@
 use bevy::prelude::*;
 // use crate::factions::Faction;
-// use serde::{Deserialize, Serialize};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::actions::{Action, Actions};
+use crate::combat::ActiveCombat;
+use crate::environment::Environment;
+use crate::game::{GameState, RunSetup, RunStart};
+use crate::rng::RunRng;
 
 pub struct ShipPlugin;
 
 impl Plugin for ShipPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, setup_player_ship)
+            .add_message::<ShipDamage>()
+            .add_message::<AdjustPower>()
+            .insert_resource(PowerSelection::default())
+            .add_systems(RunStart, setup_player_ship)
             .add_systems(Update, (
                 update_ship_systems,
                 handle_ship_damage,
-                update_power_distribution,
-            ));
+                handle_power_input,
+                (apply_power_changes, update_power_distribution).chain(),
+            ).run_if(in_state(GameState::Playing)));
     }
 }
 
 #[derive(Component)]
 pub struct PlayerShip;
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Ship {
     pub systems: ShipSystems,
     pub weapons: Vec<Weapon>,
+    pub hull: f32,
+    pub max_hull: f32,
+    // Ammunition shared by every missile launcher aboard
+    #[serde(default)]
+    pub missiles: u32,
 }
 
+// Weapon mounts on any ship
+pub const MAX_WEAPONS: usize = 4;
+
+impl Ship {
+    pub fn is_destroyed(&self) -> bool {
+        self.hull <= 0.0
+    }
 
-#[derive(Component, Clone)]
+    pub fn install_weapon(&mut self, weapon: Weapon) -> Result<(), String> {
+        if self.weapons.len() >= MAX_WEAPONS {
+            return Err("no free weapon mounts".to_string());
+        }
+        self.weapons.push(weapon);
+        Ok(())
+    }
+
+    // Weapons are powered in mount order until the weapons system runs out of power
+    pub fn powered_weapons(&self) -> usize {
+        let mut power = self.systems.weapons.active_level();
+        let mut powered = 0;
+        for weapon in &self.weapons {
+            let cost = weapon.weapon_type.power();
+            if cost > power {
+                break;
+            }
+            power -= cost;
+            powered += 1;
+        }
+        powered
+    }
+}
+
+
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct ShipSystems {
     pub engines: SystemModule,
     pub weapons: SystemModule,
@@ -36,12 +83,91 @@ pub struct ShipSystems {
     pub sensors: SystemModule,
 }
 
-#[derive(Clone)]
+impl ShipSystems {
+    pub fn module(&self, kind: SystemKind) -> &SystemModule {
+        match kind {
+            SystemKind::Engines => &self.engines,
+            SystemKind::Weapons => &self.weapons,
+            SystemKind::Shields => &self.shields,
+            SystemKind::Oxygen => &self.oxygen,
+            SystemKind::Medbay => &self.medbay,
+            SystemKind::Sensors => &self.sensors,
+        }
+    }
+
+    pub fn total_power_allocated(&self) -> u32 {
+        SystemKind::ALL.iter().map(|kind| self.module(*kind).power_allocated).sum()
+    }
+
+    pub fn module_mut(&mut self, kind: SystemKind) -> &mut SystemModule {
+        match kind {
+            SystemKind::Engines => &mut self.engines,
+            SystemKind::Weapons => &mut self.weapons,
+            SystemKind::Shields => &mut self.shields,
+            SystemKind::Oxygen => &mut self.oxygen,
+            SystemKind::Medbay => &mut self.medbay,
+            SystemKind::Sensors => &mut self.sensors,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SystemKind {
+    Engines,
+    Weapons,
+    Shields,
+    Oxygen,
+    Medbay,
+    Sensors,
+}
+
+impl SystemKind {
+    pub const ALL: [SystemKind; 6] = [
+        SystemKind::Engines,
+        SystemKind::Weapons,
+        SystemKind::Shields,
+        SystemKind::Oxygen,
+        SystemKind::Medbay,
+        SystemKind::Sensors,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SystemKind::Engines => "engines",
+            SystemKind::Weapons => "weapons",
+            SystemKind::Shields => "shields",
+            SystemKind::Oxygen => "oxygen",
+            SystemKind::Medbay => "medbay",
+            SystemKind::Sensors => "sensors",
+        }
+    }
+}
+
+// Highest level a system can be upgraded to
+pub const MAX_SYSTEM_LEVEL: u32 = 4;
+pub const MAX_SYSTEM_HEALTH: f32 = 100.0;
+// System health lost per point of damage that reaches it
+const SYSTEM_DAMAGE_PER_POINT: f32 = 25.0;
+// Chances for a hit that damages a system to set it on fire or breach the hull there
+const FIRE_CHANCE: f64 = 0.2;
+const BREACH_CHANCE: f64 = 0.15;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SystemModule {
     pub level: u32,
     pub power_allocated: u32,
     pub health: f32,
     pub efficiency: f32, // 0.0 to 1.0
+    #[serde(default)]
+    pub hazards: SystemHazards,
+}
+
+// Fires and breaches in a system's compartment, and how far crew are in dealing with them
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SystemHazards {
+    pub fire: bool,
+    pub breach: bool,
+    pub progress: f32,
 }
 
 impl SystemModule {
@@ -49,8 +175,18 @@ impl SystemModule {
         Self {
             level: 1,
             power_allocated: 1,
-            health: 100.0,
+            health: MAX_SYSTEM_HEALTH,
             efficiency: 1.0,
+            hazards: SystemHazards::default(),
+        }
+    }
+
+    // A fully powered module of the given level
+    pub fn with_level(level: u32) -> Self {
+        Self {
+            level,
+            power_allocated: level,
+            ..Self::new()
         }
     }
 
@@ -59,20 +195,118 @@ impl SystemModule {
     }
 
     pub fn effective_level(&self) -> f32 {
+        self.active_level() as f32 * self.efficiency
+    }
+
+    // Levels that are installed, undamaged and powered
+    pub fn active_level(&self) -> u32 {
         if !self.is_functional() {
-            return 0.0;
+            return 0;
         }
-        (self.power_allocated as f32).min(self.level as f32) * self.efficiency
+        self.power_allocated.min(self.capacity())
+    }
+
+    // Levels that can still take power; each lost quarter of health knocks out a share
+    pub fn capacity(&self) -> u32 {
+        (self.level as f32 * self.health / MAX_SYSTEM_HEALTH).ceil() as u32
+    }
+
+    pub fn take_damage(&mut self, amount: f32) {
+        self.health = (self.health - amount * SYSTEM_DAMAGE_PER_POINT).max(0.0);
     }
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Weapon {
+    #[serde(default)]
+    pub weapon_type: WeaponType,
     pub charge_time: f32,
     pub current_charge: f32,
 }
 
-#[derive(Component)]
+impl Weapon {
+    pub fn new(weapon_type: WeaponType) -> Self {
+        Self {
+            weapon_type,
+            charge_time: weapon_type.charge_time(),
+            current_charge: 0.0,
+        }
+    }
+
+    pub fn is_charged(&self) -> bool {
+        self.current_charge >= self.charge_time
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WeaponType {
+    // Several light shots; each one is stopped by a shield layer
+    #[default]
+    Laser,
+    // One heavy shot that flies through shields, costs a missile
+    Missile,
+    // Strips shield layers, or knocks power out of an unshielded system
+    Ion,
+    // Sweeps across two systems, weakened by every shield layer
+    Beam,
+    // Celestial-tech lance that burns through a shield layer and still hits
+    AetheriumLance,
+}
+
+impl WeaponType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponType::Laser => "Burst Laser",
+            WeaponType::Missile => "Missile Launcher",
+            WeaponType::Ion => "Ion Blaster",
+            WeaponType::Beam => "Sweep Beam",
+            WeaponType::AetheriumLance => "Aetherium Lance",
+        }
+    }
+
+    pub fn charge_time(&self) -> f32 {
+        match self {
+            WeaponType::Laser => 3.0,
+            WeaponType::Missile => 4.0,
+            WeaponType::Ion => 3.5,
+            WeaponType::Beam => 5.0,
+            WeaponType::AetheriumLance => 8.0,
+        }
+    }
+
+    // Damage of each shot
+    pub fn damage(&self) -> f32 {
+        match self {
+            WeaponType::Laser => 1.0,
+            WeaponType::Missile => 2.0,
+            WeaponType::Ion => 0.0,
+            WeaponType::Beam => 2.0,
+            WeaponType::AetheriumLance => 3.0,
+        }
+    }
+
+    pub fn shots(&self) -> u32 {
+        match self {
+            WeaponType::Laser => 2,
+            _ => 1,
+        }
+    }
+
+    // Weapons system power needed to keep it charging
+    pub fn power(&self) -> u32 {
+        match self {
+            WeaponType::Laser | WeaponType::Missile | WeaponType::Ion => 1,
+            WeaponType::Beam => 2,
+            WeaponType::AetheriumLance => 3,
+        }
+    }
+
+    pub fn uses_missiles(&self) -> bool {
+        matches!(self, WeaponType::Missile)
+    }
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Shields {
     pub current: f32,
     pub max: f32,
@@ -81,34 +315,138 @@ pub struct Shields {
     pub last_hit_time: f32,
 }
 
-#[derive(Resource)]
+// A single shot landing on a ship; how shields deal with it depends on the weapon
+#[derive(Message, Clone)]
+pub struct ShipDamage {
+    pub target: Entity,
+    pub amount: f32,
+    // System struck if the hit gets through the shields
+    pub system: Option<SystemKind>,
+    pub weapon: WeaponType,
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct PowerDistribution {
     pub total_power: u32,
     pub available_power: u32,
 }
 
-fn setup_player_ship(mut commands: Commands) {
+// Moves reactor power into (positive) or out of (negative) one of the player's systems
+#[derive(Message, Clone)]
+pub struct AdjustPower {
+    pub system: SystemKind,
+    pub delta: i32,
+}
+
+// System highlighted on the power panel for keyboard control
+#[derive(Resource, Default)]
+pub struct PowerSelection {
+    pub selected: usize,
+}
+
+impl PowerSelection {
+    pub fn system(&self) -> SystemKind {
+        SystemKind::ALL[self.selected % SystemKind::ALL.len()]
+    }
+}
+
+// Hulls the player can start a run in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StartingShip {
+    #[default]
+    Drifter,
+    Interceptor,
+    Bulwark,
+}
+
+impl StartingShip {
+    pub const ALL: [StartingShip; 3] = [
+        StartingShip::Drifter,
+        StartingShip::Interceptor,
+        StartingShip::Bulwark,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StartingShip::Drifter => "Drifter",
+            StartingShip::Interceptor => "Interceptor",
+            StartingShip::Bulwark => "Bulwark",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            StartingShip::Drifter => "Balanced: burst laser, two shield layers",
+            StartingShip::Interceptor => "Fast and hard-hitting: laser and ion cannon, thin hull",
+            StartingShip::Bulwark => "Heavy: three shield layers and a missile launcher, slow",
+        }
+    }
+
+    fn build(&self) -> Ship {
+        // Every layout draws 8 power or less, so the whole ship starts powered
+        match self {
+            StartingShip::Drifter => Ship {
+                systems: ShipSystems {
+                    engines: SystemModule::new(),
+                    weapons: SystemModule::new(),
+                    // Two shield levels give the drifter two layers from the start
+                    shields: SystemModule::with_level(2),
+                    oxygen: SystemModule::new(),
+                    medbay: SystemModule::new(),
+                    sensors: SystemModule::new(),
+                },
+                weapons: vec![
+                    Weapon::new(WeaponType::Laser),
+                ],
+                hull: 30.0,
+                max_hull: 30.0,
+                missiles: 6,
+            },
+            StartingShip::Interceptor => Ship {
+                systems: ShipSystems {
+                    engines: SystemModule::with_level(2),
+                    weapons: SystemModule::with_level(2),
+                    shields: SystemModule::new(),
+                    oxygen: SystemModule::new(),
+                    medbay: SystemModule::new(),
+                    sensors: SystemModule::new(),
+                },
+                weapons: vec![
+                    Weapon::new(WeaponType::Laser),
+                    Weapon::new(WeaponType::Ion),
+                ],
+                hull: 24.0,
+                max_hull: 24.0,
+                missiles: 2,
+            },
+            StartingShip::Bulwark => Ship {
+                systems: ShipSystems {
+                    engines: SystemModule::new(),
+                    weapons: SystemModule::new(),
+                    shields: SystemModule::with_level(3),
+                    oxygen: SystemModule::new(),
+                    medbay: SystemModule::new(),
+                    sensors: SystemModule::new(),
+                },
+                weapons: vec![
+                    Weapon::new(WeaponType::Missile),
+                ],
+                hull: 40.0,
+                max_hull: 40.0,
+                missiles: 10,
+            },
+        }
+    }
+}
+
+fn setup_player_ship(mut commands: Commands, run_setup: Res<RunSetup>) {
     // Create the player's starting ship based on Cosmicrafts lore
-    let ship = Ship {
-        systems: ShipSystems {
-            engines: SystemModule::new(),
-            weapons: SystemModule::new(),
-            shields: SystemModule::new(),
-            oxygen: SystemModule::new(),
-            medbay: SystemModule::new(),
-            sensors: SystemModule::new(),
-        },
-        weapons: vec![
-            Weapon {
-                charge_time: 2.0,
-                current_charge: 0.0,
-            }
-        ],
-    };
+    let ship = run_setup.ship.build();
 
+    let shield_layers = ship.systems.shields.level as f32;
     let shields = Shields {
-        current: 2.0,
-        max: 2.0,
+        current: shield_layers,
+        max: shield_layers,
         recharge_rate: 1.0,
         recharge_delay: 5.0,
         last_hit_time: 0.0,
@@ -130,23 +468,42 @@ fn setup_player_ship(mut commands: Commands) {
 
 fn update_ship_systems(
     mut ships: Query<(&mut Ship, &mut Shields)>,
+    active_combat: Res<ActiveCombat>,
+    environment: Res<Environment>,
     time: Res<Time>,
 ) {
     for (mut ship, mut shields) in ships.iter_mut() {
-        // Update weapon charging
-        for weapon in &mut ship.weapons {
-            if weapon.current_charge < weapon.charge_time {
-                weapon.current_charge += time.delta_secs();
+        // Weapons only charge in a fight, so every fight starts from empty guns.
+        // Guns the weapons system can't power lose their charge, and a damaged
+        // weapons system charges the rest more slowly
+        let powered_weapons = ship.powered_weapons();
+        let charge_rate = ship.systems.weapons.efficiency;
+        for (i, weapon) in ship.weapons.iter_mut().enumerate() {
+            if i >= powered_weapons || !active_combat.is_active() {
+                weapon.current_charge = 0.0;
+            } else if weapon.current_charge < weapon.charge_time {
+                weapon.current_charge += time.delta_secs() * charge_rate;
             }
         }
 
+        // Each active shields level is one shield layer
+        shields.max = ship.systems.shields.active_level() as f32;
+        shields.current = shields.current.min(shields.max);
+
         // Update shield recharge
         let current_time = time.elapsed_secs();
-        if current_time - shields.last_hit_time > shields.recharge_delay {
-            if shields.current < shields.max {
-                let shield_power = ship.systems.shields.effective_level();
-                shields.current = (shields.current + shields.recharge_rate * shield_power * time.delta_secs())
-                    .min(shields.max);
+        if current_time - shields.last_hit_time > shields.recharge_delay && shields.current < shields.max {
+            let shield_power = ship.systems.shields.effective_level() * environment.shield_regen_multiplier();
+            shields.current = (shields.current + shields.recharge_rate * shield_power * time.delta_secs())
+                .min(shields.max);
+        }
+
+        // Damaged levels can't hold power, which goes back to the reactor
+        for kind in SystemKind::ALL {
+            let module = ship.systems.module_mut(kind);
+            let capacity = module.capacity();
+            if module.power_allocated > capacity {
+                module.power_allocated = capacity;
             }
         }
 
@@ -166,10 +523,134 @@ fn update_system_efficiency(system: &mut SystemModule) {
 }
 
 fn handle_ship_damage(
-    // This system will handle incoming damage to ships
-    // For now, it's a placeholder for the combat system
+    mut damage_reader: MessageReader<ShipDamage>,
+    mut ships: Query<(&mut Ship, &mut Shields, Has<PlayerShip>)>,
+    mut run_rng: ResMut<RunRng>,
+    time: Res<Time>,
 ) {
-    // Placeholder for damage handling
+    for damage in damage_reader.read() {
+        let Ok((mut ship, mut shields, is_player)) = ships.get_mut(damage.target) else {
+            continue;
+        };
+
+        // Any hit resets the shield recharge delay
+        shields.last_hit_time = time.elapsed_secs();
+
+        let amount = match damage.weapon {
+            // Each full shield layer absorbs one shot entirely
+            WeaponType::Laser => {
+                if shields.current >= 1.0 {
+                    shields.current -= 1.0;
+                    continue;
+                }
+                damage.amount
+            }
+            WeaponType::Missile => damage.amount,
+            // Ion never hurts the hull; it drains shields first, then system power
+            WeaponType::Ion => {
+                if shields.current >= 1.0 {
+                    shields.current -= 1.0;
+                } else if let Some(system) = damage.system {
+                    let module = ship.systems.module_mut(system);
+                    module.power_allocated = module.power_allocated.saturating_sub(1);
+                    println!("An ion blast knocks power out of the {}!", system.name());
+                }
+                continue;
+            }
+            WeaponType::Beam => damage.amount - shields.current.floor(),
+            WeaponType::AetheriumLance => {
+                shields.current = (shields.current - 1.0).max(0.0);
+                damage.amount
+            }
+        };
+        if amount <= 0.0 {
+            continue;
+        }
+
+        let Some(system) = damage.system else {
+            ship.hull = (ship.hull - amount).max(0.0);
+            continue;
+        };
+
+        // Beams cut through the struck system and the next one along
+        let mut struck = vec![system];
+        if damage.weapon == WeaponType::Beam {
+            let index = SystemKind::ALL.iter().position(|kind| *kind == system).unwrap_or(0);
+            struck.push(SystemKind::ALL[(index + 1) % SystemKind::ALL.len()]);
+        }
+
+        // The hull takes the hit once, however many systems it sweeps
+        ship.hull = (ship.hull - amount).max(0.0);
+        for system in struck {
+            let module = ship.systems.module_mut(system);
+            module.take_damage(amount);
+
+            // Only the player's ship models compartments and crew to deal with hazards
+            if is_player {
+                let rng = &mut run_rng.combat;
+                if !module.hazards.fire && rng.gen_bool(FIRE_CHANCE) {
+                    module.hazards.fire = true;
+                    println!("Fire in the {} compartment!", system.name());
+                }
+                if !module.hazards.breach && rng.gen_bool(BREACH_CHANCE) {
+                    module.hazards.breach = true;
+                    println!("Hull breach in the {} compartment!", system.name());
+                }
+            }
+        }
+    }
+}
+
+fn handle_power_input(
+    actions: Res<Actions>,
+    mut selection: ResMut<PowerSelection>,
+    mut power_writer: MessageWriter<AdjustPower>,
+) {
+    let count = SystemKind::ALL.len();
+    if actions.just_performed(Action::SelectPreviousSystem) {
+        selection.selected = (selection.selected + count - 1) % count;
+    }
+    if actions.just_performed(Action::SelectNextSystem) {
+        selection.selected = (selection.selected + 1) % count;
+    }
+
+    if actions.just_performed(Action::AddPower) {
+        power_writer.write(AdjustPower { system: selection.system(), delta: 1 });
+    }
+    if actions.just_performed(Action::RemovePower) {
+        power_writer.write(AdjustPower { system: selection.system(), delta: -1 });
+    }
+}
+
+fn apply_power_changes(
+    mut power_reader: MessageReader<AdjustPower>,
+    power_dist: Res<PowerDistribution>,
+    mut ships: Query<&mut Ship, With<PlayerShip>>,
+) {
+    let Ok(mut ship) = ships.single_mut() else { return; };
+
+    for change in power_reader.read() {
+        // Recount each time so several changes in one frame can't overdraw the reactor
+        let available = power_dist.total_power.saturating_sub(ship.systems.total_power_allocated());
+        let module = ship.systems.module_mut(change.system);
+
+        if change.delta > 0 {
+            if module.power_allocated >= module.capacity() && module.capacity() < module.level {
+                println!("{} are too damaged to take more power.", change.system.name());
+            } else if module.power_allocated >= module.level {
+                println!("{} can't take more power until upgraded.", change.system.name());
+            } else if available == 0 {
+                println!("No reactor power left for {}.", change.system.name());
+            } else {
+                module.power_allocated += 1;
+            }
+        } else if change.delta < 0 && module.power_allocated > 0 {
+            module.power_allocated -= 1;
+            if module.power_allocated == 0 {
+                println!("{} offline.", change.system.name());
+            }
+        }
+    }
 }
 
 fn update_power_distribution(
@@ -177,16 +658,13 @@ fn update_power_distribution(
     mut ships: Query<&mut Ship, With<PlayerShip>>,
 ) {
     if let Ok(ship) = ships.single_mut() {
-        // Calculate total power usage
-        let total_used = ship.systems.engines.power_allocated
-            + ship.systems.weapons.power_allocated
-            + ship.systems.shields.power_allocated
-            + ship.systems.oxygen.power_allocated
-            + ship.systems.medbay.power_allocated
-            + ship.systems.sensors.power_allocated;
+        let total_used = ship.systems.total_power_allocated();
 
-        power_dist.available_power = power_dist.total_power.saturating_sub(total_used);
+        // Only write on change so the power readout isn't rebuilt every frame
+        let available_power = power_dist.total_power.saturating_sub(total_used);
+        if power_dist.available_power != available_power {
+            power_dist.available_power = available_power;
+        }
     }
 }
 
-// Combat-related functions
@@ -0,0 +1,202 @@
+use bevy::app::PluginsState;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::autopilot::AutopilotPlugin;
+use crate::game::{GameData, GameLogicPlugin, GameState, RunStats};
+use crate::rng::{RunRng, seed_from_args};
+use crate::sector::SectorMap;
+use crate::ship::{PlayerShip, Ship};
+
+// Game time that passes per simulated frame
+const SIM_STEP: f32 = 0.2;
+// Frames before a run that hasn't ended is given up on (an hour of game time)
+const MAX_FRAMES: u32 = 18_000;
+const DEFAULT_RUNS: u32 = 100;
+const DEFAULT_STATS_PATH: &str = "simulations/runs.ron";
+
+pub struct SimulationOptions {
+    pub runs: u32,
+    pub base_seed: u64,
+    pub stats_path: PathBuf,
+}
+
+impl SimulationOptions {
+    // `--headless [--runs 500] [--seed 1234] [--stats path.ron]`; None starts the normal game
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        if !args.iter().any(|arg| arg == "--headless") {
+            return None;
+        }
+
+        Some(Self {
+            runs: arg_value(&args, "--runs").and_then(|value| value.parse().ok()).unwrap_or(DEFAULT_RUNS),
+            base_seed: seed_from_args().unwrap_or_else(rand::random),
+            stats_path: arg_value(&args, "--stats").map(PathBuf::from).unwrap_or_else(|| DEFAULT_STATS_PATH.into()),
+        })
+    }
+}
+
+// Accepts `--name value` or `--name=value`
+fn arg_value(args: &[String], name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+        if arg == name {
+            return args.get(i + 1).cloned();
+        }
+    }
+    None
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum RunOutcome {
+    #[default]
+    Unfinished,
+    Victory,
+    Defeat,
+    // Out of fuel with nowhere left to refuel
+    Stranded,
+    // Neither won nor lost within MAX_FRAMES, e.g. stuck on an event it can't afford
+    TimedOut,
+}
+
+// Everything recorded about one simulated run
+#[derive(Resource, Serialize, Default)]
+pub struct RunReport {
+    pub seed: u64,
+    pub outcome: RunOutcome,
+    pub sectors_reached: u32,
+    pub enemies_destroyed: u32,
+    pub scrap_collected: u32,
+    pub cause_of_death: Option<String>,
+    pub game_seconds: f32,
+    // Sampled on arrival at every sector, starting with the first
+    pub danger: Vec<u32>,
+    pub fuel: Vec<f32>,
+    pub scrap: Vec<u32>,
+    pub hull: Vec<f32>,
+}
+
+// A windowless app with only the gameplay rules. Nothing reads a real keyboard;
+// whoever drives the app presses keys on `ButtonInput<KeyCode>` directly.
+pub fn headless_app(seed: u64) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((MinimalPlugins, StatesPlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        // Every frame is a fixed slice of game time, however fast it runs
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(SIM_STEP)))
+        .insert_resource(RunRng::new(seed))
+        .add_plugins(GameLogicPlugin)
+        // Straight into the run, past the menus
+        .insert_state(GameState::Playing);
+    app
+}
+
+// Readies an app that will be stepped with `update` by hand. App::run does
+// this itself, but never hands control back.
+pub fn finish_building(app: &mut App) {
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+}
+
+pub fn run(options: SimulationOptions) {
+    let reports: Vec<RunReport> = (0..options.runs)
+        .map(|run| simulate_run(options.base_seed.wrapping_add(run as u64)))
+        .collect();
+
+    let victories = reports.iter().filter(|report| report.outcome == RunOutcome::Victory).count();
+    let average_sectors = reports.iter().map(|report| report.sectors_reached as f32).sum::<f32>()
+        / reports.len().max(1) as f32;
+    println!(
+        "Simulated {} runs from seed {}: {} victories, {:.1} sectors reached on average.",
+        reports.len(), options.base_seed, victories, average_sectors,
+    );
+
+    match write_reports(&options.stats_path, &reports) {
+        Ok(()) => println!("Run statistics written to {}", options.stats_path.display()),
+        Err(err) => println!("Could not write run statistics: {}", err),
+    }
+}
+
+fn write_reports(path: &Path, reports: &[RunReport]) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, ron::ser::to_string_pretty(reports, ron::ser::PrettyConfig::default())?)?;
+    Ok(())
+}
+
+fn simulate_run(seed: u64) -> RunReport {
+    let mut app = headless_app(seed);
+    app
+        .add_plugins(AutopilotPlugin)
+        .insert_resource(RunReport { seed, ..default() })
+        .add_systems(PostUpdate, sample_arrivals);
+
+    finish_building(&mut app);
+
+    let mut outcome = RunOutcome::Unfinished;
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        outcome = match app.world().resource::<State<GameState>>().get() {
+            GameState::Victory => RunOutcome::Victory,
+            GameState::GameOver => RunOutcome::Defeat,
+            _ => continue,
+        };
+        break;
+    }
+
+    let world = app.world_mut();
+    if outcome == RunOutcome::Unfinished {
+        outcome = if world.resource::<GameData>().fuel < 1.0 { RunOutcome::Stranded } else { RunOutcome::TimedOut };
+    }
+
+    let run_stats = world.resource::<RunStats>().clone();
+    let sectors_reached = world.resource::<SectorMap>().distance_traveled;
+    let game_seconds = world.resource::<Time>().elapsed_secs();
+
+    let mut report = world.remove_resource::<RunReport>().unwrap_or_default();
+    report.outcome = outcome;
+    report.sectors_reached = sectors_reached;
+    report.enemies_destroyed = run_stats.enemies_destroyed;
+    report.scrap_collected = run_stats.scrap_collected;
+    report.cause_of_death = match outcome {
+        RunOutcome::Stranded => Some("Stranded without fuel".to_string()),
+        _ => run_stats.cause_of_death,
+    };
+    report.game_seconds = game_seconds;
+    report
+}
+
+fn sample_arrivals(
+    mut report: ResMut<RunReport>,
+    mut last_distance: Local<Option<u32>>,
+    sector_map: Res<SectorMap>,
+    game_data: Res<GameData>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+) {
+    if *last_distance == Some(sector_map.distance_traveled) {
+        return;
+    }
+    let Ok(ship) = player_query.single() else { return; };
+    *last_distance = Some(sector_map.distance_traveled);
+
+    let danger = sector_map.sectors.get(&sector_map.current_sector_id)
+        .map(|sector| sector.danger_level)
+        .unwrap_or(0);
+    report.danger.push(danger);
+    report.fuel.push(game_data.fuel);
+    report.scrap.push(game_data.scrap);
+    report.hull.push(ship.hull);
+}
@@ -0,0 +1,322 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::combat::ActiveCombat;
+use crate::crew::CrewRoster;
+use crate::actions::{Action, Actions, Bindings};
+use crate::events::ActiveEvent;
+use crate::factions::{Faction, FactionRelations, RelationLevel};
+use crate::game::{GameData, GameState};
+use crate::rng::RunRng;
+use crate::sector::{SectorMap, SectorType};
+use crate::ship::{PlayerShip, PowerDistribution, Ship, SystemKind, Weapon, WeaponType, MAX_SYSTEM_LEVEL};
+use crate::upgrades::{UpgradeScreen, install_level};
+
+pub struct StorePlugin;
+
+impl Plugin for StorePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Store::default())
+            .add_systems(Update, (
+                open_store_at_stations,
+                handle_store_input,
+            ).chain()
+                .after(crate::events::EventSystemSet)
+                .before(crate::sector::NavigationSystemSet)
+                .run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Scrap charged per hull point repaired at a station
+pub const REPAIR_COST_PER_HULL: u32 = 2;
+// Hull points restored per repair order
+const REPAIR_STEP: f32 = 5.0;
+// Scrap charged per unit of fuel
+const FUEL_COST: u32 = 3;
+// Missiles sold per crate
+const MISSILE_CRATE: u32 = 3;
+
+const RECRUIT_NAMES: [&str; 8] = [
+    "Rook Talan", "Mira Quell", "Dex-9", "Sora Venn",
+    "Ilya Marr", "Tobin Hask", "Nyx Ardent", "Cael Voss",
+];
+
+#[derive(Clone)]
+pub enum StoreItemKind {
+    Fuel(f32),
+    HullRepair,
+    Weapon(WeaponType),
+    Missiles(u32),
+    SystemUpgrade(SystemKind),
+    Crew(String),
+    MapData,
+}
+
+#[derive(Clone)]
+pub struct StoreItem {
+    pub kind: StoreItemKind,
+    pub price: u32,
+    pub sold: bool,
+}
+
+impl StoreItem {
+    // Fuel and repairs never run out; everything else is a single offer
+    pub fn is_restocked(&self) -> bool {
+        matches!(self.kind, StoreItemKind::Fuel(_) | StoreItemKind::HullRepair)
+    }
+
+    pub fn label(&self) -> String {
+        match &self.kind {
+            StoreItemKind::Fuel(amount) => format!("Fuel +{:.0}", amount),
+            StoreItemKind::HullRepair => format!("Hull repair +{:.0}", REPAIR_STEP),
+            StoreItemKind::Weapon(weapon_type) => format!("{} ({} power)", weapon_type.name(), weapon_type.power()),
+            StoreItemKind::Missiles(count) => format!("Missiles x{}", count),
+            StoreItemKind::SystemUpgrade(system) => format!("Upgrade {}", system.name()),
+            StoreItemKind::Crew(name) => format!("Hire {}", name),
+            StoreItemKind::MapData => "Map data".to_string(),
+        }
+    }
+
+    // What buying it costs right now; a repair that tops up less than a full
+    // step costs only its share of the price
+    pub fn cost(&self, ship: &Ship) -> u32 {
+        match self.kind {
+            StoreItemKind::HullRepair => {
+                let repaired = (ship.max_hull - ship.hull).clamp(0.0, REPAIR_STEP);
+                (self.price as f32 * repaired / REPAIR_STEP).ceil() as u32
+            }
+            _ => self.price,
+        }
+    }
+}
+
+// The store of the station the player is currently docked at
+#[derive(Resource, Default)]
+pub struct Store {
+    pub open: bool,
+    pub sector_id: Option<u32>,
+    pub owner: Option<Faction>,
+    pub price_multiplier: f32,
+    pub items: Vec<StoreItem>,
+}
+
+// Stations charge by how the owning faction sees the player; hostile ones won't trade
+pub fn price_multiplier(standing: &RelationLevel) -> Option<f32> {
+    match standing {
+        RelationLevel::Hostile => None,
+        RelationLevel::Unfriendly => Some(1.25),
+        RelationLevel::Neutral => Some(1.0),
+        RelationLevel::Friendly => Some(0.9),
+        RelationLevel::Allied => Some(0.75),
+    }
+}
+
+fn generate_stock(danger_level: u32, multiplier: f32, rng: &mut impl Rng) -> Vec<StoreItem> {
+    let priced = |base: u32| (base as f32 * multiplier).round().max(1.0) as u32;
+    let mut items = vec![
+        StoreItem { kind: StoreItemKind::Fuel(1.0), price: priced(FUEL_COST), sold: false },
+        StoreItem {
+            kind: StoreItemKind::HullRepair,
+            price: priced(REPAIR_COST_PER_HULL * REPAIR_STEP as u32),
+            sold: false,
+        },
+    ];
+
+    // Every station sells charts of the surrounding space
+    items.push(StoreItem { kind: StoreItemKind::MapData, price: priced(15 + danger_level * 2), sold: false });
+
+    // Deeper stations carry more, and better, stock at higher prices
+    let extra_items = (2 + danger_level / 2).min(5);
+    for _ in 0..extra_items {
+        let (kind, base_price) = match rng.gen_range(0..4) {
+            0 => {
+                let choices = weapons_for_danger(danger_level);
+                let weapon_type = choices[rng.gen_range(0..choices.len())];
+                (StoreItemKind::Weapon(weapon_type), weapon_price(weapon_type) + danger_level * 5)
+            }
+            1 => {
+                let system = SystemKind::ALL[rng.gen_range(0..SystemKind::ALL.len())];
+                (StoreItemKind::SystemUpgrade(system), 25 + danger_level * 4)
+            }
+            2 => (StoreItemKind::Missiles(MISSILE_CRATE), 12 + danger_level),
+            _ => {
+                let name = RECRUIT_NAMES[rng.gen_range(0..RECRUIT_NAMES.len())];
+                (StoreItemKind::Crew(name.to_string()), 30 + danger_level * 3)
+            }
+        };
+        items.push(StoreItem { kind, price: priced(base_price), sold: false });
+    }
+
+    items
+}
+
+// Heavier weapons only turn up at stations deeper into the rift
+fn weapons_for_danger(danger_level: u32) -> Vec<WeaponType> {
+    let mut weapons = vec![WeaponType::Laser, WeaponType::Missile, WeaponType::Ion];
+    if danger_level >= 2 {
+        weapons.push(WeaponType::Beam);
+    }
+    if danger_level >= 4 {
+        weapons.push(WeaponType::AetheriumLance);
+    }
+    weapons
+}
+
+fn weapon_price(weapon_type: WeaponType) -> u32 {
+    match weapon_type {
+        WeaponType::Laser => 40,
+        WeaponType::Missile | WeaponType::Ion => 45,
+        WeaponType::Beam => 60,
+        WeaponType::AetheriumLance => 90,
+    }
+}
+
+fn open_store_at_stations(
+    mut store: ResMut<Store>,
+    sector_map: Res<SectorMap>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+    relations: Res<FactionRelations>,
+    bindings: Res<Bindings>,
+    mut run_rng: ResMut<RunRng>,
+) {
+    // The station's own event is dealt with before its store opens
+    if active_event.event.is_some() || active_combat.is_active() {
+        return;
+    }
+
+    let current_id = sector_map.current_sector_id;
+    if store.sector_id == Some(current_id) {
+        return;
+    }
+
+    let Some(sector) = sector_map.sectors.get(&current_id) else { return; };
+    if sector.sector_type != SectorType::Station {
+        if store.sector_id.is_some() {
+            *store = Store::default();
+        }
+        return;
+    }
+
+    let owner = sector.owner.clone().unwrap_or(Faction::Neutral);
+    store.sector_id = Some(current_id);
+    store.owner = Some(owner.clone());
+
+    let Some(multiplier) = price_multiplier(&relations.standing(&owner)) else {
+        println!("{} refuses to trade with you.", sector.name);
+        store.open = false;
+        store.items.clear();
+        return;
+    };
+
+    store.price_multiplier = multiplier;
+    store.items = generate_stock(sector.danger_level, multiplier, &mut run_rng.store);
+    // Docking doesn't open the store, so the number keys keep picking jumps until the player asks to trade
+    store.open = false;
+    println!("Docked at {} ({} station). {} opens the store.", sector.name, owner.name(), bindings.label(Action::ToggleStore));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_store_input(
+    actions: Res<Actions>,
+    mut store: ResMut<Store>,
+    mut upgrade_screen: ResMut<UpgradeScreen>,
+    mut game_data: ResMut<GameData>,
+    mut crew: ResMut<CrewRoster>,
+    mut power: ResMut<PowerDistribution>,
+    mut player_query: Query<&mut Ship, With<PlayerShip>>,
+    mut sector_map: ResMut<SectorMap>,
+    mut run_rng: ResMut<RunRng>,
+) {
+    // Opens and closes the store of a station that is willing to trade
+    if actions.just_performed(Action::ToggleStore) && !store.items.is_empty() {
+        store.open = !store.open;
+        // Only one of the two screens is shown at a time
+        if store.open {
+            upgrade_screen.open = false;
+        }
+        return;
+    }
+
+    if !store.open {
+        return;
+    }
+
+    let Some(index) = actions.index_of(Action::Buy) else { return; };
+    let Ok(mut ship) = player_query.single_mut() else { return; };
+    let Some(item) = store.items.get_mut(index) else { return; };
+    match purchase(item, &mut game_data, &mut ship, &mut power, &mut crew, &mut sector_map, &mut run_rng.store) {
+        Ok(message) => println!("{}", message),
+        Err(reason) => println!("Cannot buy {}: {}", item.label(), reason),
+    }
+}
+
+fn purchase(
+    item: &mut StoreItem,
+    game_data: &mut GameData,
+    ship: &mut Ship,
+    power: &mut PowerDistribution,
+    crew: &mut CrewRoster,
+    sector_map: &mut SectorMap,
+    rng: &mut impl Rng,
+) -> Result<String, String> {
+    if item.sold {
+        return Err("sold out".to_string());
+    }
+    let cost = item.cost(ship);
+    if game_data.scrap < cost {
+        return Err(format!("requires {} scrap", cost));
+    }
+
+    let message = match &item.kind {
+        StoreItemKind::Fuel(amount) => {
+            game_data.fuel += amount;
+            format!("Bought {:.0} fuel.", amount)
+        }
+        StoreItemKind::HullRepair => {
+            let repaired = (ship.max_hull - ship.hull).min(REPAIR_STEP);
+            if repaired <= 0.0 {
+                return Err("hull is already at full integrity".to_string());
+            }
+            ship.hull += repaired;
+            format!("Repaired {:.0} hull ({:.0}/{:.0}).", repaired, ship.hull, ship.max_hull)
+        }
+        StoreItemKind::Weapon(weapon_type) => {
+            ship.install_weapon(Weapon::new(*weapon_type))?;
+            format!("Installed a {}.", weapon_type.name())
+        }
+        StoreItemKind::Missiles(count) => {
+            ship.missiles += count;
+            format!("Loaded {} missiles ({} aboard).", count, ship.missiles)
+        }
+        StoreItemKind::SystemUpgrade(system) => {
+            let module = ship.systems.module_mut(*system);
+            if module.level >= MAX_SYSTEM_LEVEL {
+                return Err(format!("{} are already at maximum level", system.name()));
+            }
+            install_level(module, power);
+            format!("Upgraded {} to level {}.", system.name(), module.level)
+        }
+        StoreItemKind::Crew(name) => {
+            crew.recruit(name, rng);
+            format!("Hired {}.", name)
+        }
+        StoreItemKind::MapData => {
+            let mut charted = 0;
+            for sector in sector_map.sectors.values_mut().filter(|sector| !sector.visited && !sector.charted) {
+                sector.charted = true;
+                charted += 1;
+            }
+            if charted == 0 {
+                return Err("every known sector is already charted".to_string());
+            }
+            format!("Charted {} sectors.", charted)
+        }
+    };
+
+    game_data.scrap -= cost;
+    if !item.is_restocked() {
+        item.sold = true;
+    }
+    Ok(message)
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use crate::combat::{ActiveCombat, EnemyShip};
+use crate::enemy::{AiState, EnemyAi, build_enemy_ship};
+use crate::factions::{Faction, ShipClass};
+use crate::ship::{PlayerShip, Ship, ShipDamage, SystemKind, SystemModule, Weapon, WeaponType, MAX_SYSTEM_HEALTH};
+use super::TestGame;
+
+#[test]
+fn weapons_stay_empty_outside_combat() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    for _ in 0..50 {
+        game.step();
+    }
+
+    assert!(game.player_ship().weapons.iter().all(|weapon| weapon.current_charge == 0.0));
+}
+
+#[test]
+fn fights_start_with_empty_guns() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    game.start_combat();
+    assert!(game.resource::<ActiveCombat>().is_active());
+
+    // One frame of charge at most, far from a full volley
+    let world = game.world_mut();
+    let enemy = world.query_filtered::<&Ship, With<EnemyShip>>().single(world).unwrap().clone();
+    for weapon in game.player_ship().weapons.iter().chain(&enemy.weapons) {
+        assert!(!weapon.is_charged());
+    }
+}
+
+// A laser and a beam need three power; the guns' charge after a few frames of fighting
+fn charges_with_weapon_power(power: u32) -> Vec<f32> {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    {
+        let world = game.world_mut();
+        let mut ship = world.query_filtered::<&mut Ship, With<PlayerShip>>().single_mut(world).unwrap();
+        ship.systems.weapons = SystemModule::with_level(3);
+        ship.systems.weapons.power_allocated = power;
+        ship.weapons = vec![Weapon::new(WeaponType::Laser), Weapon::new(WeaponType::Beam)];
+    }
+    game.start_combat();
+    for _ in 0..10 {
+        game.step();
+    }
+    game.player_ship().weapons.iter().map(|weapon| weapon.current_charge).collect()
+}
+
+#[test]
+fn short_power_shuts_off_later_guns_only() {
+    let full = charges_with_weapon_power(3);
+    let short = charges_with_weapon_power(1);
+
+    // The laser is still fed and charges at full speed, two seconds in ten frames;
+    // the beam gets nothing
+    assert!(full[1] > 0.0);
+    assert!(short[0] >= 1.9, "laser charged {} on one power", short[0]);
+    assert_eq!(short[1], 0.0);
+}
+
+#[test]
+fn every_enemy_ship_carries_an_energy_weapon() {
+    let factions = [Faction::Cosmicons, Faction::Spirats, Faction::Webes, Faction::Celestials, Faction::Spades, Faction::Archs, Faction::Neutral];
+    let classes = [ShipClass::Scout, ShipClass::Fighter, ShipClass::Cruiser, ShipClass::Battleship, ShipClass::Flagship];
+    for faction in &factions {
+        for class in classes {
+            for danger in [0, 5, 10] {
+                let (ship, _) = build_enemy_ship(faction, class, danger);
+                assert!(ship.weapons.iter().any(|weapon| !weapon.weapon_type.uses_missiles()));
+            }
+        }
+    }
+}
+
+#[test]
+fn enemy_out_of_missiles_tries_to_escape() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    game.start_combat();
+    {
+        let world = game.world_mut();
+        let mut ship = world.query_filtered::<&mut Ship, With<EnemyShip>>().single_mut(world).unwrap();
+        ship.weapons = vec![Weapon::new(WeaponType::Missile)];
+        ship.missiles = 0;
+    }
+    game.step();
+
+    let world = game.world_mut();
+    let ai = world.query_filtered::<&EnemyAi, With<EnemyShip>>().single(world).unwrap();
+    assert_eq!(ai.state, AiState::Escaping);
+}
+
+#[test]
+fn beams_hit_the_hull_once_per_shot() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let world = game.world_mut();
+    let (player, mut ship) = world.query_filtered::<(Entity, &mut Ship), With<PlayerShip>>().single_mut(world).unwrap();
+    // No shields to soak the beam
+    ship.systems.shields.power_allocated = 0;
+    let hull = ship.hull;
+    game.step();
+    game.world_mut().write_message(ShipDamage {
+        target: player,
+        amount: 2.0,
+        system: Some(SystemKind::Engines),
+        weapon: WeaponType::Beam,
+    });
+    game.step();
+
+    // Both swept systems are damaged, but the hull only loses the shot's damage
+    let ship = game.player_ship();
+    assert_eq!(ship.hull, hull - 2.0);
+    assert!(ship.systems.engines.health < MAX_SYSTEM_HEALTH);
+    assert!(ship.systems.weapons.health < MAX_SYSTEM_HEALTH);
+}
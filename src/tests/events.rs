@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use crate::events::{EventOutcome, EventRequirement, RandomBranch, outcome_preview};
+use super::{TestGame, choice};
+
+#[test]
+fn choosing_an_event_option_does_not_also_navigate() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    game.open_event(vec![choice(EventOutcome::Continue, vec![])]);
+    let fuel = game.game_data().fuel;
+
+    // Digit1 is also the key for the sector's first connection
+    game.press(KeyCode::Digit1);
+
+    assert!(game.active_event().is_none());
+    assert_eq!(game.sector_map().current_sector_id, 0);
+    assert_eq!(game.sector_map().distance_traveled, 0);
+    assert_eq!(game.game_data().fuel, fuel);
+}
+
+#[test]
+fn unmet_requirement_keeps_the_event_open() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let reward = EventOutcome::Reward { scrap: 10, fuel: 0.0, crew: None, missiles: 0 };
+    game.open_event(vec![choice(reward, vec![EventRequirement::Fuel(1000.0)])]);
+    let scrap = game.game_data().scrap;
+
+    game.press(KeyCode::Digit1);
+
+    assert!(game.active_event().is_some());
+    assert_eq!(game.game_data().scrap, scrap);
+    assert_eq!(game.sector_map().current_sector_id, 0);
+}
+
+#[test]
+fn met_requirement_applies_the_outcome() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let reward = EventOutcome::Reward { scrap: 10, fuel: 0.0, crew: None, missiles: 0 };
+    game.open_event(vec![choice(reward, vec![EventRequirement::Fuel(1.0)])]);
+    let scrap = game.game_data().scrap;
+
+    game.press(KeyCode::Digit1);
+
+    assert!(game.active_event().is_none());
+    assert_eq!(game.game_data().scrap, scrap + 10);
+}
+
+#[test]
+fn rolled_branch_text_is_shown_before_its_follow_up() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let follow_up = TestGame::event("Locked Vault", vec![choice(EventOutcome::Continue, vec![])]);
+    let branch = RandomBranch {
+        chance: 1.0,
+        text: Some("The airlock gives way.".to_string()),
+        outcome: EventOutcome::FollowUp(Box::new(follow_up)),
+    };
+    game.open_event(vec![choice(EventOutcome::Random(vec![branch]), vec![])]);
+
+    game.press(KeyCode::Digit1);
+    let result = game.active_event().expect("result screen");
+    assert_eq!(result.description, "The airlock gives way.");
+    assert_eq!(result.choices.len(), 1);
+
+    game.press(KeyCode::Digit1);
+    assert_eq!(game.active_event().expect("follow-up").title, "Locked Vault");
+
+    game.press(KeyCode::Digit1);
+    assert!(game.active_event().is_none());
+}
+
+#[test]
+fn fourth_choice_can_be_picked() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let reward = EventOutcome::Reward { scrap: 10, fuel: 0.0, crew: None, missiles: 0 };
+    let mut choices = vec![choice(EventOutcome::Continue, vec![]); 3];
+    choices.push(choice(reward, vec![]));
+    game.open_event(choices);
+    let scrap = game.game_data().scrap;
+
+    game.press(KeyCode::Digit4);
+
+    assert!(game.active_event().is_none());
+    assert_eq!(game.game_data().scrap, scrap + 10);
+}
+
+#[test]
+fn known_outcomes_are_previewed() {
+    let reward = EventOutcome::Reward { scrap: 10, fuel: 2.0, crew: None, missiles: 0 };
+    let loss = EventOutcome::Loss { scrap: 0, fuel: 0.0, hull_damage: 5.0 };
+    assert_eq!(outcome_preview(&reward).as_deref(), Some("+10 scrap, +2.0 fuel"));
+    assert_eq!(
+        outcome_preview(&EventOutcome::Chain(vec![reward, EventOutcome::Continue, loss])).as_deref(),
+        Some("+10 scrap, +2.0 fuel, 5 hull damage"),
+    );
+    assert_eq!(outcome_preview(&EventOutcome::Continue), None);
+}
@@ -0,0 +1,133 @@
+// Gameplay flows driven through a headless app, frame by frame
+
+mod combat;
+mod events;
+mod navigation;
+mod runs;
+mod store;
+
+use bevy::prelude::*;
+use crate::combat::StartCombat;
+use crate::events::{ActiveEvent, EventChoice, EventOutcome, EventRequirement, GameEvent, GameEventType};
+use crate::game::{GameData, GameState};
+use crate::factions::{Faction, ShipClass};
+use crate::sector::{SectorMap, SectorType};
+use crate::ship::{PlayerShip, Ship};
+use crate::simulation::{finish_building, headless_app};
+use crate::store::Store;
+
+pub struct TestGame {
+    app: App,
+}
+
+impl TestGame {
+    // A fresh run on a fixed seed, past its startup systems
+    pub fn new() -> Self {
+        let mut app = headless_app(1234);
+        finish_building(&mut app);
+        app.update();
+        Self { app }
+    }
+
+    pub fn step(&mut self) {
+        self.app.update();
+    }
+
+    // Holds the key down for exactly one frame, as a real key press would
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(key);
+        self.step();
+        self.app.world_mut().resource_mut::<ButtonInput<KeyCode>>().reset_all();
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        self.app.world_mut().resource_mut::<R>()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn state(&self) -> &GameState {
+        self.resource::<State<GameState>>().get()
+    }
+
+    pub fn sector_map(&self) -> &SectorMap {
+        self.resource::<SectorMap>()
+    }
+
+    pub fn game_data(&self) -> &GameData {
+        self.resource::<GameData>()
+    }
+
+    pub fn active_event(&self) -> Option<&GameEvent> {
+        self.resource::<ActiveEvent>().event.as_ref()
+    }
+
+    // Puts the ship in a quiet sector with nothing pending, clear to navigate
+    pub fn move_to_quiet_sector(&mut self, sector_id: u32) {
+        let mut sector_map = self.resource_mut::<SectorMap>();
+        sector_map.current_sector_id = sector_id;
+        let sector = sector_map.sectors.get_mut(&sector_id).expect("sector exists");
+        // An empty sector, so there is no store to dock at
+        sector.sector_type = SectorType::Empty;
+        sector.visited = true;
+        sector.events.clear();
+        self.resource_mut::<ActiveEvent>().event = None;
+        *self.resource_mut::<Store>() = Store::default();
+    }
+
+    // Picks a fight with a small ship that needs a frame to spawn
+    pub fn start_combat(&mut self) {
+        self.world_mut().write_message(StartCombat {
+            enemy_faction: Faction::Spirats,
+            difficulty: 1,
+            ship_class: Some(ShipClass::Scout),
+        });
+        self.step();
+    }
+
+    pub fn player_ship(&mut self) -> Ship {
+        let world = self.world_mut();
+        world.query_filtered::<&Ship, With<PlayerShip>>().single(world).expect("player ship").clone()
+    }
+
+    pub fn open_event(&mut self, choices: Vec<EventChoice>) {
+        self.resource_mut::<ActiveEvent>().event = Some(Self::event("Test Event", choices));
+    }
+
+    pub fn event(title: &str, choices: Vec<EventChoice>) -> GameEvent {
+        GameEvent {
+            _event_type: GameEventType::Story,
+            title: title.to_string(),
+            description: "A test event.".to_string(),
+            choices,
+            _faction: None,
+        }
+    }
+
+    // The forward jumps (to existing sectors) out of the current sector, with their key index
+    pub fn forward_connections(&self) -> Vec<(usize, u32)> {
+        let sector_map = self.sector_map();
+        let current_id = sector_map.current_sector_id;
+        sector_map.sectors[&current_id].connections.iter().copied().enumerate()
+            .filter(|(_, id)| *id > current_id && sector_map.sectors.contains_key(id))
+            .collect()
+    }
+}
+
+pub fn choice(outcome: EventOutcome, requirements: Vec<EventRequirement>) -> EventChoice {
+    EventChoice { text: "Test choice".to_string(), outcome, requirements }
+}
+
+pub fn number_key(index: usize) -> KeyCode {
+    [
+        KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+        KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6,
+        KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    ][index]
+}
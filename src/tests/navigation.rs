@@ -0,0 +1,73 @@
+use super::{TestGame, number_key};
+
+#[test]
+fn number_key_travels_to_a_connected_sector() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let (index, target_id) = game.forward_connections()[0];
+    let fuel = game.game_data().fuel;
+
+    game.press(number_key(index));
+
+    assert_eq!(game.sector_map().current_sector_id, target_id);
+    assert_eq!(game.sector_map().distance_traveled, 1);
+    assert_eq!(game.game_data().fuel, fuel - 1.0);
+    assert!(game.sector_map().sectors[&target_id].visited);
+}
+
+#[test]
+fn travel_is_refused_below_one_fuel() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    game.resource_mut::<crate::game::GameData>().fuel = 0.5;
+    let (index, _) = game.forward_connections()[0];
+
+    game.press(number_key(index));
+
+    assert_eq!(game.sector_map().current_sector_id, 0);
+    assert_eq!(game.sector_map().distance_traveled, 0);
+    assert_eq!(game.game_data().fuel, 0.5);
+}
+
+#[test]
+fn reaching_the_last_layer_generates_the_next_map() {
+    let mut game = TestGame::new();
+    // Last-layer sectors end their connections with the id of a map not generated yet
+    let (last_layer_id, exit_id) = game.sector_map().sectors.iter()
+        .filter_map(|(id, sector)| {
+            let exit_id = *sector.connections.last()?;
+            (!game.sector_map().sectors.contains_key(&exit_id)).then_some((*id, exit_id))
+        })
+        .min()
+        .expect("the map has a last layer");
+    let sectors_before = game.sector_map().sectors.len();
+
+    game.move_to_quiet_sector(last_layer_id);
+    game.step();
+
+    let sector_map = game.sector_map();
+    assert_eq!(sector_map.current_sector_id, exit_id);
+    assert_eq!(sector_map.distance_traveled, 1);
+    assert!(sector_map.sectors.len() > sectors_before);
+
+    // The new map is reachable from its entry and has an exit of its own
+    let entry = &sector_map.sectors[&exit_id];
+    assert!(!entry.connections.is_empty());
+    assert!(entry.connections.iter().all(|id| sector_map.sectors.contains_key(id)));
+    let next_exit = sector_map.sectors.values()
+        .filter_map(|sector| sector.connections.last())
+        .find(|id| !sector_map.sectors.contains_key(id));
+    assert!(next_exit.is_some_and(|id| *id > exit_id));
+}
+
+#[test]
+fn no_travel_while_an_event_is_open() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    game.open_event(Vec::new());
+    let (index, _) = game.forward_connections()[0];
+
+    game.press(number_key(index));
+
+    assert_eq!(game.sector_map().current_sector_id, 0);
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use crate::game::{Difficulty, GameState, RunSetup, start_new_run};
+use crate::rng::RunRng;
+use crate::ship::{PlayerShip, Ship, StartingShip};
+use super::{TestGame, number_key};
+
+#[test]
+fn new_run_uses_the_chosen_setup() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    let (index, _) = game.forward_connections()[0];
+    game.press(number_key(index));
+    assert_eq!(game.sector_map().distance_traveled, 1);
+
+    game.world_mut().insert_resource(RunSetup {
+        ship: StartingShip::Bulwark,
+        difficulty: Difficulty::Hard,
+        seed: 99,
+    });
+    start_new_run(game.world_mut());
+    game.step();
+
+    assert_eq!(*game.state(), GameState::Playing);
+    assert_eq!(game.sector_map().distance_traveled, 0);
+    assert_eq!(game.sector_map().current_sector_id, 0);
+    assert_eq!(game.resource::<RunRng>().seed, 99);
+    assert_eq!(game.game_data().fuel, 40.0);
+    assert_eq!(game.game_data().scrap, 5);
+
+    // The old ship is gone and the new one is a Bulwark
+    let hulls: Vec<f32> = game.world_mut()
+        .query_filtered::<&Ship, With<PlayerShip>>()
+        .iter(game.world_mut())
+        .map(|ship| ship.max_hull)
+        .collect();
+    assert_eq!(hulls, vec![40.0]);
+}
+
+#[test]
+fn pausing_freezes_navigation_and_weapons() {
+    let mut game = TestGame::new();
+    game.move_to_quiet_sector(0);
+    // Weapons only charge in a fight
+    game.start_combat();
+    game.step();
+    assert!(game.player_ship().weapons[0].current_charge > 0.0);
+
+    // The state changes on the frame after the key press
+    game.press(KeyCode::Escape);
+    game.step();
+    assert_eq!(*game.state(), GameState::Paused);
+
+    let paused_charge = game.player_ship().weapons[0].current_charge;
+    let (index, _) = game.forward_connections()[0];
+    game.press(number_key(index));
+    game.step();
+
+    assert_eq!(game.sector_map().distance_traveled, 0);
+    assert_eq!(game.player_ship().weapons[0].current_charge, paused_charge);
+
+    game.press(KeyCode::Escape);
+    game.step();
+    assert_eq!(*game.state(), GameState::Playing);
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use crate::game::GameData;
+use crate::sector::{SectorMap, SectorType};
+use crate::ship::{PlayerShip, Ship};
+use crate::store::{Store, StoreItemKind};
+use super::{TestGame, number_key};
+
+// Docked at a station with its store generated, but not yet opened
+fn dock_at_station(game: &mut TestGame) {
+    game.move_to_quiet_sector(0);
+    game.resource_mut::<SectorMap>().sectors.get_mut(&0).unwrap().sector_type = SectorType::Station;
+    game.step();
+    assert_eq!(game.resource::<Store>().sector_id, Some(0));
+}
+
+#[test]
+fn docking_leaves_the_number_keys_on_jumps() {
+    let mut game = TestGame::new();
+    dock_at_station(&mut game);
+    assert!(!game.resource::<Store>().open);
+    let (index, target_id) = game.forward_connections()[0];
+
+    game.press(number_key(index));
+
+    assert_eq!(game.sector_map().current_sector_id, target_id);
+}
+
+#[test]
+fn partial_repair_costs_its_share() {
+    let mut game = TestGame::new();
+    dock_at_station(&mut game);
+    game.press(KeyCode::KeyS);
+    assert!(game.resource::<Store>().open);
+
+    let (index, price) = game.resource::<Store>().items.iter().enumerate()
+        .find(|(_, item)| matches!(item.kind, StoreItemKind::HullRepair))
+        .map(|(index, item)| (index, item.price))
+        .expect("stations sell repairs");
+    let world = game.world_mut();
+    let mut ship = world.query_filtered::<&mut Ship, With<PlayerShip>>().single_mut(world).unwrap();
+    ship.hull = ship.max_hull - 1.0;
+    game.resource_mut::<GameData>().scrap = 100;
+
+    game.press(number_key(index));
+
+    // One hull point out of a five point repair
+    assert_eq!(game.game_data().scrap, 100 - price.div_ceil(5));
+    assert_eq!(game.player_ship().hull, game.player_ship().max_hull);
+}
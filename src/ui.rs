@@ -1,6 +1,20 @@
 use bevy::prelude::*;
-use crate::game::{GameState, GameData};
-use crate::events::ActiveEvent;
+use crate::actions::{Action, Actions, Bindings};
+use crate::game::{GameState, GameData, RunStats, VICTORY_DISTANCE};
+use crate::events::{ActiveEvent, EventSystemSet, outcome_preview, requirement_failures};
+use crate::crew::{CrewRoster, Skill};
+use crate::factions::{Faction, FactionRelations};
+use crate::rng::RunRng;
+use crate::combat::{ActiveCombat, EnemyShip, WeaponControl};
+use crate::enemy::{AiState, EnemyAi};
+use crate::ship::{AdjustPower, PlayerShip, PowerDistribution, PowerSelection, Ship, Shields, SystemKind};
+use crate::sector::NavigationSystemSet;
+use crate::store::Store;
+use crate::life_support::{LifeSupport, SUFFOCATION_THRESHOLD};
+use crate::damage_control::CrewSelection;
+use crate::environment::Environment;
+use crate::sensors::{Detail, SensorScan};
+use crate::upgrades::{UpgradeScreen, level_effect, reactor_cost, upgrade_cost};
 
 pub struct UIPlugin;
 
@@ -10,8 +24,23 @@ impl Plugin for UIPlugin {
             .add_systems(Startup, setup_ui)
             .add_systems(Update, (
                 update_hud,
-                update_event_ui.run_if(in_state(GameState::Playing)),
-                update_sector_info.run_if(in_state(GameState::Playing)),
+                // Keeps a clicked choice's button around until the map has seen the click
+                update_event_ui.after(NavigationSystemSet),
+                highlight_event_choices,
+                handle_event_choice_clicks.before(EventSystemSet),
+                update_sector_info,
+                update_combat_ui,
+                update_crew_ui,
+                update_faction_ui,
+                update_store_ui,
+                update_upgrade_ui,
+                update_power_ui,
+                handle_power_buttons,
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::GameOver), spawn_run_summary)
+            .add_systems(OnEnter(GameState::Victory), spawn_run_summary)
+            .add_systems(Update, close_run_summary.run_if(
+                in_state(GameState::GameOver).or(in_state(GameState::Victory))
             ));
     }
 }
@@ -20,12 +49,54 @@ impl Plugin for UIPlugin {
 struct HudText;
 
 #[derive(Component)]
-struct EventText;
+struct EventPanel;
+
+// One button per choice of the open event
+#[derive(Component)]
+struct EventChoiceButton {
+    index: usize,
+    available: bool,
+}
+
+const CHOICE_COLOR: Color = Color::srgb(0.18, 0.18, 0.12);
+const CHOICE_HOVER_COLOR: Color = Color::srgb(0.32, 0.32, 0.2);
+const CHOICE_PRESSED_COLOR: Color = Color::srgb(0.45, 0.45, 0.28);
+const CHOICE_BLOCKED_COLOR: Color = Color::srgb(0.12, 0.12, 0.12);
 
 #[derive(Component)]
 struct SectorText;
 
-fn setup_ui(mut commands: Commands) {
+#[derive(Component)]
+struct CombatText;
+
+#[derive(Component)]
+struct CrewText;
+
+#[derive(Component)]
+struct FactionText;
+
+#[derive(Component)]
+struct StoreText;
+
+#[derive(Component)]
+struct UpgradeText;
+
+#[derive(Component)]
+struct PowerRowText(SystemKind);
+
+#[derive(Component)]
+struct ReactorText;
+
+#[derive(Component)]
+struct PowerButton {
+    system: SystemKind,
+    delta: i32,
+}
+
+#[derive(Component)]
+struct RunSummaryScreen;
+
+fn setup_ui(mut commands: Commands, bindings: Res<Bindings>) {
     // HUD Elements
     commands.spawn((
         HudText,
@@ -60,9 +131,28 @@ fn setup_ui(mut commands: Commands) {
         },
     ));
 
-    // Controls
+    // Controls, as bound in the bindings file
+    let key = |action| bindings.label(action);
     commands.spawn((
-        Text::new("Controls: 1-9 - Travel to Exit | Click Node - Travel | 1-3 - Event Choices | ESC - Pause"),
+        Text::new(format!(
+            "Controls: {} - Event Choices / Travel / Buy | Click Choice - Choose | Click Node - Travel | {} - Store (Station) | {} - Upgrades | {}/{}/{}/{} - Power | {}/{} - Crew Stations | {} - Target | {} - Autofire | {} - Fire | {} - Save | {} - Load | {} - Load Autosave | {} - Pause",
+            bindings.number_label(),
+            key(Action::ToggleStore),
+            key(Action::ToggleUpgrades),
+            key(Action::SelectPreviousSystem),
+            key(Action::SelectNextSystem),
+            key(Action::RemovePower),
+            key(Action::AddPower),
+            key(Action::CycleCrew),
+            key(Action::AssignCrew),
+            key(Action::CycleTarget),
+            key(Action::ToggleAutofire),
+            key(Action::Fire),
+            key(Action::Quicksave),
+            key(Action::Quickload),
+            key(Action::LoadAutosave),
+            key(Action::Pause),
+        )),
         TextFont {
             font_size: 16.0,
             ..default()
@@ -76,15 +166,47 @@ fn setup_ui(mut commands: Commands) {
         },
     ));
 
-    // Event UI - abajo a la derecha
+    // Event dialog - abajo a la derecha, filled in while an event is open
+    commands.spawn((
+        EventPanel,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(100.0),
+            right: px(10.0),
+            width: px(400.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(4.0),
+            ..default()
+        },
+    ));
+
+    // Station store - same place as events, which never show at the same time
+    commands.spawn((
+        StoreText,
+        Text::new(""),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 1.0, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(100.0),
+            right: px(10.0),
+            width: px(400.0),
+            ..default()
+        },
+    ));
+
+    // Ship upgrades - same place as the store, only one is open at a time
     commands.spawn((
-        EventText,
+        UpgradeText,
         Text::new(""),
         TextFont {
             font_size: 18.0,
             ..default()
         },
-        TextColor(Color::srgb(1.0, 1.0, 0.8)),
+        TextColor(Color::srgb(0.8, 0.9, 1.0)),
         Node {
             position_type: PositionType::Absolute,
             bottom: px(100.0),
@@ -93,54 +215,306 @@ fn setup_ui(mut commands: Commands) {
             ..default()
         },
     ));
+
+    // Power panel - izquierda, debajo de la tripulacion
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: px(180.0),
+            left: px(10.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(2.0),
+            ..default()
+        })
+        .with_children(|panel| {
+            panel.spawn((
+                ReactorText,
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.9, 0.5)),
+            ));
+
+            for system in SystemKind::ALL {
+                panel
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: px(4.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        spawn_power_button(row, system, -1);
+                        spawn_power_button(row, system, 1);
+                        row.spawn((
+                            PowerRowText(system),
+                            Text::new(""),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.8, 0.9, 1.0)),
+                        ));
+                    });
+            }
+        });
+
+    // Crew roster - debajo del HUD
+    commands.spawn((
+        CrewText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.7, 0.9, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(45.0),
+            left: px(10.0),
+            ..default()
+        },
+    ));
+
+    // Faction standings - derecha, debajo del combate
+    commands.spawn((
+        FactionText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.8, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(130.0),
+            right: px(10.0),
+            ..default()
+        },
+    ));
+
+    // Combat status - arriba a la derecha
+    commands.spawn((
+        CombatText,
+        Text::new(""),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.6, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(10.0),
+            right: px(10.0),
+            width: px(400.0),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_power_button(row: &mut ChildSpawnerCommands, system: SystemKind, delta: i32) {
+    row.spawn((
+        Button,
+        PowerButton { system, delta },
+        Node {
+            width: px(20.0),
+            height: px(20.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.2, 0.25, 0.35)),
+        children![(
+            Text::new(if delta > 0 { "+" } else { "-" }),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        )],
+    ));
 }
 
 fn update_hud(
-    mut hud_query: Query<&mut TextSpan, With<HudText>>,
+    mut hud_query: Query<&mut Text, With<HudText>>,
     game_data: Res<GameData>,
     sector_map: Res<crate::sector::SectorMap>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    life_support: Res<LifeSupport>,
+    environment: Res<Environment>,
+    run_rng: Res<RunRng>,
 ) {
-    if let Ok(mut span) = hud_query.single_mut() {
-        **span = format!(
-            "Fuel: {:.1} | Scrap: {} | Distance: {}",
+    if let Ok(mut text) = hud_query.single_mut() {
+        let (hull, max_hull) = player_query.single()
+            .map(|ship| (ship.hull, ship.max_hull))
+            .unwrap_or((0.0, 0.0));
+
+        **text = format!(
+            "Hull: {:.0}/{:.0} | O2: {:.0}%{} | Fuel: {:.1} | Scrap: {} | Distance: {}/{} | Armada: {} | Seed: {}",
+            hull,
+            max_hull,
+            life_support.oxygen,
+            if life_support.oxygen < SUFFOCATION_THRESHOLD { " LOW" } else { "" },
             game_data.fuel,
             game_data.scrap,
-            sector_map.distance_traveled
+            sector_map.distance_traveled,
+            VICTORY_DISTANCE,
+            match sector_map.armada_gap() {
+                gap if gap <= 0 => "HERE".to_string(),
+                gap => format!("{} jumps behind", gap),
+            },
+            run_rng.seed
         );
+        if let Some(hazard) = environment.hazard {
+            text.push_str(&format!(" | Hazard: {}", hazard.name()));
+        }
     }
 }
 
 fn update_event_ui(
-    mut event_query: Query<&mut Text, With<EventText>>,
+    mut commands: Commands,
+    panel_query: Query<Entity, With<EventPanel>>,
     active_event: Res<ActiveEvent>,
+    game_data: Res<GameData>,
+    crew: Res<CrewRoster>,
+    mut shown_failures: Local<Vec<Vec<String>>>,
 ) {
-    if let Ok(mut text) = event_query.single_mut() {
-        if let Some(event) = &active_event.event {
-            let mut event_text = format!("{}\n{}\n\nChoices:\n", event.title, event.description);
-            
-            for (i, choice) in event.choices.iter().enumerate() {
-                event_text.push_str(&format!("{}. {}\n", i + 1, choice.text));
-            }
-            
-            *text = Text::new(event_text);
-        } else {
-            *text = Text::new("");
+    if !(active_event.is_changed() || game_data.is_changed() || crew.is_changed()) {
+        return;
+    }
+
+    let failures: Vec<Vec<String>> = active_event.event.iter()
+        .flat_map(|event| &event.choices)
+        .map(|choice| requirement_failures(&choice.requirements, &game_data, &crew))
+        .collect();
+    // Rebuilding the buttons resets their hover state, so only do it when something shown changed
+    if !active_event.is_changed() && failures == *shown_failures {
+        return;
+    }
+    *shown_failures = failures.clone();
+
+    let Ok(entity) = panel_query.single() else { return; };
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.despawn_children();
+
+    let Some(event) = &active_event.event else {
+        entity_commands.insert(BackgroundColor(Color::NONE));
+        return;
+    };
+    entity_commands.insert(BackgroundColor(Color::srgba(0.05, 0.05, 0.08, 0.9)));
+
+    entity_commands.with_children(|panel| {
+        panel.spawn((
+            Text::new(format!("{}\n{}", event.title, event.description)),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 1.0, 0.8)),
+            Node {
+                margin: UiRect::all(px(6.0)),
+                ..default()
+            },
+        ));
+
+        for (i, (choice, failures)) in event.choices.iter().zip(&failures).enumerate() {
+            let available = failures.is_empty();
+            let (text_color, background) = if available {
+                (Color::srgb(1.0, 1.0, 0.8), CHOICE_COLOR)
+            } else {
+                (Color::srgb(0.45, 0.45, 0.45), CHOICE_BLOCKED_COLOR)
+            };
+
+            panel
+                .spawn((
+                    Button,
+                    EventChoiceButton { index: i, available },
+                    Node {
+                        padding: UiRect::all(px(6.0)),
+                        margin: UiRect::horizontal(px(6.0)),
+                        ..default()
+                    },
+                    BackgroundColor(background),
+                ))
+                .with_children(|button| {
+                    button
+                        .spawn((
+                            Text::new(format!("{}. {}", i + 1, choice.text)),
+                            TextFont {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                            TextColor(text_color),
+                        ))
+                        .with_children(|text| {
+                            if let Some(preview) = outcome_preview(&choice.outcome) {
+                                text.spawn((
+                                    TextSpan::new(format!("\n  {}", preview)),
+                                    TextColor(Color::srgb(0.6, 0.8, 0.6)),
+                                ));
+                            }
+                            for failure in failures {
+                                text.spawn((
+                                    TextSpan::new(format!("\n  {}", failure)),
+                                    TextColor(Color::srgb(0.8, 0.45, 0.45)),
+                                ));
+                            }
+                        });
+                });
+        }
+    });
+}
+
+// Blocked choices stay grey whatever the mouse does
+fn highlight_event_choices(
+    mut button_query: Query<(&Interaction, &EventChoiceButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut background) in button_query.iter_mut() {
+        background.0 = match (button.available, interaction) {
+            (false, _) => CHOICE_BLOCKED_COLOR,
+            (true, Interaction::Pressed) => CHOICE_PRESSED_COLOR,
+            (true, Interaction::Hovered) => CHOICE_HOVER_COLOR,
+            (true, Interaction::None) => CHOICE_COLOR,
+        };
+    }
+}
+
+// A click picks the choice just like its number key
+fn handle_event_choice_clicks(
+    button_query: Query<(&Interaction, &EventChoiceButton), Changed<Interaction>>,
+    mut actions: ResMut<Actions>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction == Interaction::Pressed && button.available {
+            actions.perform(Action::ChooseOption(button.index));
         }
     }
 }
 
 fn update_sector_info(
-    mut sector_query: Query<&mut TextSpan, (With<SectorText>, Without<HudText>)>,
+    mut sector_query: Query<&mut Text, (With<SectorText>, Without<HudText>)>,
     sector_map: Res<crate::sector::SectorMap>,
+    environment: Res<Environment>,
+    sensor_scan: Res<SensorScan>,
 ) {
-    if let Ok(mut span) = sector_query.single_mut() {
+    if let Ok(mut text) = sector_query.single_mut() {
         if let Some(current_sector) = sector_map.sectors.get(&sector_map.current_sector_id) {
             let mut sector_text = format!(
-                "Current Sector: {}\nType: {:?}\n{}\n\nExits: ",
+                "Current Sector: {}\nType: {:?}\n{}\n",
                 current_sector.name,
                 current_sector.sector_type,
                 current_sector.description
             );
+            if let Some(owner) = &current_sector.owner {
+                sector_text.push_str(&format!("Owner: {}\n", owner.name()));
+            }
+            if let Some(hazard) = environment.hazard {
+                sector_text.push_str(&format!("Hazard: {}\n", hazard.description()));
+            }
+            sector_text.push_str(&format!("Sensors: level {}{}\n", sensor_scan.level, if environment.sensors_jammed() { " (jammed)" } else { "" }));
+            sector_text.push_str("\nExits: ");
             
             // Show available exits
             if current_sector.connections.is_empty() {
@@ -148,16 +522,409 @@ fn update_sector_info(
             } else {
                 for (i, exit_id) in current_sector.connections.iter().enumerate() {
                     if let Some(exit_sector) = sector_map.sectors.get(exit_id) {
-                        sector_text.push_str(&format!("\n{}: {} ({:?})", i + 1, exit_sector.name, exit_sector.sector_type));
+                        // Sensors decide how much of each exit the crew can make out
+                        let reading = match sensor_scan.detail(*exit_id) {
+                            Detail::Unknown => "Unknown".to_string(),
+                            Detail::SectorType => format!("{} ({:?})", exit_sector.name, exit_sector.sector_type),
+                            Detail::Danger => format!("{} ({:?}, danger {})", exit_sector.name, exit_sector.sector_type, exit_sector.danger_level),
+                            Detail::Events => {
+                                let events = exit_sector.events.iter()
+                                    .map(|event| event.description.as_str())
+                                    .collect::<Vec<_>>();
+                                format!(
+                                    "{} ({:?}, danger {}) - {}",
+                                    exit_sector.name,
+                                    exit_sector.sector_type,
+                                    exit_sector.danger_level,
+                                    if events.is_empty() { "quiet".to_string() } else { events.join(" ") },
+                                )
+                            }
+                        };
+                        sector_text.push_str(&format!("\n{}: {}", i + 1, reading));
                     } else {
                         sector_text.push_str(&format!("\n{}: Unknown Sector", i + 1));
                     }
                 }
             }
             
-            **span = sector_text;
+            **text = sector_text;
+        } else {
+            **text = "Loading sector...".to_string();
+        }
+    }
+}
+
+fn update_store_ui(
+    mut commands: Commands,
+    store_query: Query<Entity, With<StoreText>>,
+    store: Res<Store>,
+    game_data: Res<GameData>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    upgrade_screen: Res<UpgradeScreen>,
+    bindings: Res<Bindings>,
+) {
+    if !(store.is_changed() || game_data.is_changed() || upgrade_screen.is_changed()) {
+        return;
+    }
+
+    let Ok(entity) = store_query.single() else { return; };
+    let Ok(ship) = player_query.single() else { return; };
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.despawn_children();
+
+    let Some(owner) = &store.owner else {
+        entity_commands.insert(Text::new(""));
+        return;
+    };
+
+    // Docked but not trading: the number keys still pick jumps, so say how to shop
+    if !store.open {
+        let text = if store.items.is_empty() || upgrade_screen.open {
+            String::new()
         } else {
-            **span = "Loading sector...".to_string();
+            format!("{} station - {} - Open store", owner.name(), bindings.label(Action::ToggleStore))
+        };
+        entity_commands.insert(Text::new(text));
+        return;
+    }
+
+    entity_commands.insert(Text::new(format!(
+        "STORE - {} station (prices x{:.2})\n{} - Buy\n\n",
+        owner.name(),
+        store.price_multiplier,
+        bindings.number_label()
+    )));
+
+    // Sold out and unaffordable items are greyed out like unavailable event choices
+    entity_commands.with_children(|parent| {
+        for (i, item) in store.items.iter().enumerate() {
+            let cost = item.cost(ship);
+            let (line, color) = if item.sold {
+                (format!("{}. {} (sold)\n", i + 1, item.label()), Color::srgb(0.45, 0.45, 0.45))
+            } else if game_data.scrap < cost {
+                (format!("{}. {} - {} scrap\n", i + 1, item.label(), cost), Color::srgb(0.45, 0.45, 0.45))
+            } else {
+                (format!("{}. {} - {} scrap\n", i + 1, item.label(), cost), Color::srgb(0.8, 1.0, 0.8))
+            };
+            parent.spawn((TextSpan::new(line), TextColor(color)));
+        }
+        parent.spawn((
+            TextSpan::new(format!("\n{} - Leave store", bindings.label(Action::ToggleStore))),
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ));
+    });
+}
+
+fn update_upgrade_ui(
+    mut commands: Commands,
+    upgrade_query: Query<Entity, With<UpgradeText>>,
+    screen: Res<UpgradeScreen>,
+    game_data: Res<GameData>,
+    power: Res<PowerDistribution>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    bindings: Res<Bindings>,
+) {
+    // Levels only change through purchases, which always spend scrap
+    if !(screen.is_changed() || game_data.is_changed() || power.is_changed()) {
+        return;
+    }
+    let Ok(ship) = player_query.single() else { return; };
+
+    let Ok(entity) = upgrade_query.single() else { return; };
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.despawn_children();
+
+    if !screen.open {
+        entity_commands.insert(Text::new(""));
+        return;
+    }
+
+    entity_commands.insert(Text::new("SHIP UPGRADES\n\n"));
+
+    // Upgrades the player can't afford or that are maxed out are greyed out
+    entity_commands.with_children(|parent| {
+        let mut offers: Vec<(String, Option<u32>)> = SystemKind::ALL.iter()
+            .enumerate()
+            .map(|(i, system)| {
+                let level = ship.systems.module(*system).level;
+                let cost = upgrade_cost(*system, level);
+                let line = match cost {
+                    Some(cost) => format!(
+                        "{}. {} {} -> {}: {} - {} scrap\n",
+                        i + 1, system.name(), level, level + 1, level_effect(*system, level + 1), cost
+                    ),
+                    None => format!("{}. {} {} (max): {}\n", i + 1, system.name(), level, level_effect(*system, level)),
+                };
+                (line, cost)
+            })
+            .collect();
+
+        let reactor = reactor_cost(power.total_power);
+        let reactor_line = match reactor {
+            Some(cost) => format!("7. reactor {} -> {} power - {} scrap\n", power.total_power, power.total_power + 1, cost),
+            None => format!("7. reactor {} power (max)\n", power.total_power),
+        };
+        offers.push((reactor_line, reactor));
+
+        for (line, cost) in offers {
+            let color = match cost {
+                Some(cost) if game_data.scrap >= cost => Color::srgb(0.8, 0.9, 1.0),
+                _ => Color::srgb(0.45, 0.45, 0.45),
+            };
+            parent.spawn((TextSpan::new(line), TextColor(color)));
+        }
+        parent.spawn((
+            TextSpan::new(format!("\n{} - Close upgrades", bindings.label(Action::ToggleUpgrades))),
+            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        ));
+    });
+}
+
+fn update_power_ui(
+    mut row_query: Query<(&mut Text, &PowerRowText), Without<ReactorText>>,
+    mut reactor_query: Query<&mut Text, With<ReactorText>>,
+    power: Res<PowerDistribution>,
+    selection: Res<PowerSelection>,
+    player_query: Query<&Ship, With<PlayerShip>>,
+    crew: Res<CrewRoster>,
+) {
+    let Ok(ship) = player_query.single() else { return; };
+
+    // Text is only rewritten when it differs, as the ship changes every frame
+    if let Ok(mut text) = reactor_query.single_mut() {
+        let reactor = format!("Reactor: {}/{} free", power.available_power, power.total_power);
+        if **text != reactor {
+            **text = reactor;
+        }
+    }
+
+    for (mut text, row) in row_query.iter_mut() {
+        let module = ship.systems.module(row.0);
+        let bar: String = (0..module.level)
+            .map(|bar| if bar < module.power_allocated { '#' } else { '-' })
+            .collect();
+        let marker = if selection.system() == row.0 { ">" } else { " " };
+        let mut status = String::new();
+        if module.health < crate::ship::MAX_SYSTEM_HEALTH {
+            status.push_str(&format!(" {:.0}%", module.health));
+        }
+        if !module.is_functional() {
+            status.push_str(" OFFLINE");
+        }
+        if module.hazards.fire {
+            status.push_str(" FIRE");
+        }
+        if module.hazards.breach {
+            status.push_str(" BREACH");
         }
+        let workers = crew.stationed_at(row.0);
+        if workers > 0 {
+            status.push_str(&format!(" (crew {})", workers));
+        }
+        let line = format!("{} {:<8} [{}]{}", marker, row.0.name(), bar, status);
+        if **text != line {
+            **text = line;
+        }
+    }
+}
+
+fn handle_power_buttons(
+    button_query: Query<(&Interaction, &PowerButton), Changed<Interaction>>,
+    mut selection: ResMut<PowerSelection>,
+    mut power_writer: MessageWriter<AdjustPower>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            // Clicking a row also selects it for the arrow keys
+            if let Some(index) = SystemKind::ALL.iter().position(|system| *system == button.system) {
+                selection.selected = index;
+            }
+            power_writer.write(AdjustPower { system: button.system, delta: button.delta });
+        }
+    }
+}
+
+fn update_crew_ui(
+    mut crew_query: Query<&mut Text, With<CrewText>>,
+    crew: Res<CrewRoster>,
+    selection: Res<CrewSelection>,
+) {
+    if !(crew.is_changed() || selection.is_changed()) {
+        return;
+    }
+
+    if let Ok(mut text) = crew_query.single_mut() {
+        let mut crew_text = String::from("Crew:");
+        let selected = selection.selected % crew.members.len().max(1);
+        for (i, member) in crew.members.iter().enumerate() {
+            let skills: Vec<String> = Skill::ALL.iter()
+                .map(|skill| format!("{} {}", skill.name(), member.skill_level(*skill)))
+                .collect();
+            let marker = if i == selected { ">" } else { " " };
+            let station = member.station.map(|system| system.name()).unwrap_or("idle");
+            crew_text.push_str(&format!(
+                "\n{} {} ({:.0} hp, {}) - {}",
+                marker,
+                member.name,
+                member.health,
+                station,
+                skills.join(", ")
+            ));
+        }
+        **text = crew_text;
+    }
+}
+
+fn update_faction_ui(
+    mut faction_query: Query<&mut Text, With<FactionText>>,
+    relations: Res<FactionRelations>,
+) {
+    if !relations.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = faction_query.single_mut() {
+        let mut faction_text = String::from("Standing:");
+        for faction in Faction::MAJOR {
+            faction_text.push_str(&format!(
+                "\n{}: {:?} ({:+.1})",
+                faction.name(),
+                relations.standing(&faction),
+                relations.reputation(&faction)
+            ));
+        }
+        **text = faction_text;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_combat_ui(
+    mut combat_query: Query<&mut Text, With<CombatText>>,
+    active_combat: Res<ActiveCombat>,
+    weapon_control: Res<WeaponControl>,
+    bindings: Res<Bindings>,
+    player_query: Query<(&Ship, &Shields), (With<PlayerShip>, Without<EnemyShip>)>,
+    enemy_query: Query<(&Ship, &Shields, &EnemyAi), (With<EnemyShip>, Without<PlayerShip>)>,
+) {
+    let Ok(mut text) = combat_query.single_mut() else { return; };
+
+    let (Some(enemy_entity), Some(faction)) = (active_combat.enemy, &active_combat.enemy_faction) else {
+        *text = Text::new("");
+        return;
+    };
+
+    let (Ok((player_ship, player_shields)), Ok((enemy_ship, enemy_shields, enemy_ai))) =
+        (player_query.single(), enemy_query.get(enemy_entity)) else {
+        return;
+    };
+
+    let target = active_combat.player_target.map(|system| system.name()).unwrap_or("random");
+
+    // One line per weapon mount, in the order they draw power
+    let powered = player_ship.powered_weapons();
+    let mut weapon_lines = String::new();
+    for (index, weapon) in player_ship.weapons.iter().enumerate() {
+        let status = if index >= powered {
+            "unpowered".to_string()
+        } else if weapon.weapon_type.uses_missiles() && player_ship.missiles == 0 {
+            "no missiles".to_string()
+        } else if weapon.is_charged() {
+            "READY".to_string()
+        } else {
+            format!("{:.0}%", weapon.current_charge / weapon.weapon_type.charge_time() * 100.0)
+        };
+        weapon_lines.push_str(&format!("\n  {}: {}", weapon.weapon_type.name(), status));
+    }
+
+    let answer = format!("({} - accept, {} - refuse)", bindings.label(Action::Confirm), bindings.label(Action::Cancel));
+    let enemy_status = match (enemy_ai.state, &active_combat.surrender_offer) {
+        (AiState::Surrendering, Some(offer)) => match &offer.crew {
+            Some(name) => format!("\nThey surrender: {} scrap and {} {}", offer.scrap, name, answer),
+            None => format!("\nThey surrender: {} scrap {}", offer.scrap, answer),
+        },
+        (AiState::Escaping, _) => format!("\nEnemy FTL charging: {:.0}%", enemy_ai.escape_percent()),
+        _ => String::new(),
+    };
+
+    *text = Text::new(format!(
+        "COMBAT - {} {} (difficulty {})\nTargeting: {}\nAutofire: {} | Missiles: {}{}\n\nEnemy Hull: {:.0}/{:.0} | Shields: {:.0}/{:.0}{}\nYour Hull: {:.0}/{:.0} | Shields: {:.0}/{:.0}",
+        faction.name(),
+        active_combat.enemy_class.map(|class| class.name()).unwrap_or(""),
+        active_combat.difficulty,
+        target,
+        if weapon_control.autofire { "on" } else { "off" },
+        player_ship.missiles,
+        weapon_lines,
+        enemy_ship.hull,
+        enemy_ship.max_hull,
+        enemy_shields.current.floor(),
+        enemy_shields.max,
+        enemy_status,
+        player_ship.hull,
+        player_ship.max_hull,
+        player_shields.current.floor(),
+        player_shields.max,
+    ));
+}
+
+fn spawn_run_summary(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    run_stats: Res<RunStats>,
+    game_data: Res<GameData>,
+    sector_map: Res<crate::sector::SectorMap>,
+    run_rng: Res<RunRng>,
+    bindings: Res<Bindings>,
+) {
+    let (title, color) = match state.get() {
+        GameState::Victory => ("VICTORY - You escaped the Dark Rift!", Color::srgb(0.6, 1.0, 0.6)),
+        _ => ("GAME OVER - Your ship was lost", Color::srgb(1.0, 0.5, 0.5)),
+    };
+
+    let mut summary = format!(
+        "{}\n\nSectors traveled: {}/{}\nEnemies destroyed: {}\nScrap collected: {}\nScrap remaining: {}\nFuel remaining: {:.1}",
+        title,
+        sector_map.distance_traveled,
+        VICTORY_DISTANCE,
+        run_stats.enemies_destroyed,
+        run_stats.scrap_collected,
+        game_data.scrap,
+        game_data.fuel,
+    );
+    if let Some(cause) = &run_stats.cause_of_death {
+        summary.push_str(&format!("\nCause: {}", cause));
+    }
+    summary.push_str(&format!("\nSeed: {}", run_rng.seed));
+    summary.push_str(&format!("\n\nPress {} to return to the main menu", bindings.label(Action::Confirm)));
+
+    commands.spawn((
+        RunSummaryScreen,
+        DespawnOnExit(state.get().clone()),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100.0),
+            height: percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        children![(
+            Text::new(summary),
+            TextFont {
+                font_size: 28.0,
+                ..default()
+            },
+            TextColor(color),
+        )],
+    ));
+}
+
+fn close_run_summary(
+    actions: Res<Actions>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_performed(Action::Confirm) {
+        next_state.set(GameState::MainMenu);
     }
 }
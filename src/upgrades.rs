@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use crate::combat::ActiveCombat;
+use crate::actions::{Action, Actions};
+use crate::events::ActiveEvent;
+use crate::game::{GameData, GameState};
+use crate::ship::{PlayerShip, PowerDistribution, Ship, SystemKind, SystemModule, MAX_SYSTEM_LEVEL};
+use crate::store::Store;
+
+pub struct UpgradesPlugin;
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(UpgradeScreen::default())
+            .add_systems(Update, handle_upgrade_input
+                .after(crate::events::EventSystemSet)
+                .before(crate::sector::NavigationSystemSet)
+                .run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Largest reactor the drifter can be fitted with
+pub const MAX_REACTOR_POWER: u32 = 16;
+
+// The reactor is listed after the systems on the upgrade screen
+const REACTOR_INDEX: usize = SystemKind::ALL.len();
+
+#[derive(Resource, Default)]
+pub struct UpgradeScreen {
+    pub open: bool,
+}
+
+// Scrap needed to raise a system from the given level, or None at the cap
+pub fn upgrade_cost(system: SystemKind, level: u32) -> Option<u32> {
+    if level >= MAX_SYSTEM_LEVEL {
+        return None;
+    }
+
+    // Cost of each step up from level 1, 2 and 3
+    let costs: [u32; 3] = match system {
+        SystemKind::Engines => [20, 35, 55],
+        SystemKind::Weapons => [30, 50, 75],
+        SystemKind::Shields => [40, 65, 95],
+        SystemKind::Oxygen => [15, 25, 40],
+        SystemKind::Medbay => [20, 30, 45],
+        SystemKind::Sensors => [15, 30, 45],
+    };
+    costs.get(level.saturating_sub(1) as usize).copied()
+}
+
+// Scrap needed for one more reactor bar, or None at the cap
+pub fn reactor_cost(total_power: u32) -> Option<u32> {
+    if total_power >= MAX_REACTOR_POWER {
+        return None;
+    }
+    Some(20 + 5 * total_power.saturating_sub(8))
+}
+
+// What a system does at a given level, for the upgrade screen
+pub fn level_effect(system: SystemKind, level: u32) -> String {
+    match system {
+        SystemKind::Engines => format!("{}% evasion", 10 + 5 * level),
+        SystemKind::Weapons => format!("{} weapon power", level),
+        SystemKind::Shields => format!("{} shield layer(s)", level),
+        SystemKind::Oxygen => format!("refills {}% oxygen/s", 2 * level),
+        SystemKind::Medbay => format!("heals crew {} hp/s", level),
+        SystemKind::Sensors => match level {
+            1 => "sector types 1 jump out".to_string(),
+            2 => "danger levels 2 jumps out".to_string(),
+            _ => format!("pending events {} jumps out", level),
+        },
+    }
+}
+
+// Adds a level to a system; it comes online straight away if the reactor has power to spare
+pub fn install_level(module: &mut SystemModule, power: &mut PowerDistribution) {
+    module.level += 1;
+    if power.available_power > 0 {
+        module.power_allocated += 1;
+        power.available_power -= 1;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_upgrade_input(
+    actions: Res<Actions>,
+    mut screen: ResMut<UpgradeScreen>,
+    mut store: ResMut<Store>,
+    mut game_data: ResMut<GameData>,
+    mut power: ResMut<PowerDistribution>,
+    mut player_query: Query<&mut Ship, With<PlayerShip>>,
+    active_event: Res<ActiveEvent>,
+    active_combat: Res<ActiveCombat>,
+) {
+    // Refits happen between jumps, never mid-event or mid-fight
+    if active_event.event.is_some() || active_combat.is_active() {
+        screen.open = false;
+        return;
+    }
+
+    if actions.just_performed(Action::ToggleUpgrades) {
+        screen.open = !screen.open;
+        // Only one of the two screens is shown at a time
+        if screen.open {
+            store.open = false;
+        }
+        return;
+    }
+
+    if !screen.open {
+        return;
+    }
+
+    let Some(index) = actions.index_of(Action::Upgrade) else { return; };
+    let Ok(mut ship) = player_query.single_mut() else { return; };
+
+    if let Some(system) = SystemKind::ALL.get(index) {
+        let module = ship.systems.module_mut(*system);
+        let Some(cost) = upgrade_cost(*system, module.level) else {
+            println!("{} are already at maximum level.", system.name());
+            return;
+        };
+        if game_data.scrap < cost {
+            println!("Upgrading {} requires {} scrap.", system.name(), cost);
+            return;
+        }
+
+        game_data.scrap -= cost;
+        install_level(module, &mut power);
+        println!("Upgraded {} to level {} ({}).", system.name(), module.level, level_effect(*system, module.level));
+        return;
+    }
+
+    if index == REACTOR_INDEX {
+        let Some(cost) = reactor_cost(power.total_power) else {
+            println!("The reactor is already at maximum output.");
+            return;
+        };
+        if game_data.scrap < cost {
+            println!("A reactor upgrade requires {} scrap.", cost);
+            return;
+        }
+
+        game_data.scrap -= cost;
+        power.total_power += 1;
+        power.available_power += 1;
+        println!("Reactor upgraded to {} power.", power.total_power);
+    }
+}